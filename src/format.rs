@@ -0,0 +1,72 @@
+// How numbers are written on the board and in the result, per the player's settings.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) struct FormatOpts {
+    // `12,345` instead of `12345`.
+    pub(crate) group_thousands: bool,
+    // `+5` instead of `5`, so the sign is never lost against the board.
+    pub(crate) explicit_sign: bool,
+}
+
+pub(crate) fn format_number(n: i64, opts: &FormatOpts) -> String {
+    let digits = n.unsigned_abs().to_string();
+    let digits = if opts.group_thousands {
+        group(&digits)
+    } else {
+        digits
+    };
+    let sign = if n < 0 {
+        "-"
+    } else if n > 0 && opts.explicit_sign {
+        "+"
+    } else {
+        ""
+    };
+    format!("{}{}", sign, digits)
+}
+
+fn group(digits: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAIN: FormatOpts = FormatOpts {
+        group_thousands: false,
+        explicit_sign: false,
+    };
+    const ALL: FormatOpts = FormatOpts {
+        group_thousands: true,
+        explicit_sign: true,
+    };
+
+    #[test]
+    fn plain_matches_display() {
+        for n in [0, 7, -7, 1234567, i64::MIN, i64::MAX] {
+            assert_eq!(format_number(n, &PLAIN), n.to_string());
+        }
+    }
+
+    #[test]
+    fn groups_every_three_digits() {
+        assert_eq!(format_number(999, &ALL), "+999");
+        assert_eq!(format_number(1000, &ALL), "+1,000");
+        assert_eq!(format_number(-1234567, &ALL), "-1,234,567");
+    }
+
+    #[test]
+    fn zero_has_no_sign() {
+        assert_eq!(format_number(0, &ALL), "0");
+    }
+}
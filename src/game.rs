@@ -0,0 +1,477 @@
+// The board rules, kept free of the DOM so they can be tested natively.
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+// What a board can hold: the integers normally, exact fractions in rational mode.
+pub(crate) trait Value: Copy + PartialEq {
+    // `None` if the result can't be represented.
+    fn combine(operator: Operator, lhs: Self, rhs: Self) -> Option<Self>;
+}
+
+impl Value for i64 {
+    // Division only goes through when it's exact.
+    fn combine(operator: Operator, lhs: Self, rhs: Self) -> Option<Self> {
+        match operator {
+            Operator::Add => lhs.checked_add(rhs),
+            Operator::Sub => lhs.checked_sub(rhs),
+            Operator::Mul => lhs.checked_mul(rhs),
+            Operator::Div => (rhs != 0 && lhs.checked_rem(rhs)? == 0).then(|| lhs / rhs),
+        }
+    }
+}
+
+// A single combine: `numbers[target] = previous <operator> amount` where `amount` was the
+// value of `source`. `previous` is kept because multiplying by zero can't be inverted.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Move<V = i64> {
+    pub(crate) source: usize,
+    pub(crate) target: usize,
+    pub(crate) operator: Operator,
+    pub(crate) amount: V,
+    pub(crate) previous: V,
+    // Milliseconds from the first move, stamped when the game clock is running.
+    #[serde(default)]
+    pub(crate) elapsed_ms: Option<u32>,
+}
+
+impl<V: Value> Move<V> {
+    // `None` if the result overflows.
+    pub(crate) fn result(&self) -> Option<V> {
+        self.operator.apply(self.previous, self.amount)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Operator {
+    Add,
+    Sub,
+    Mul,
+    // Only offered in rational mode.
+    Div,
+}
+
+impl Operator {
+    pub(crate) fn apply<V: Value>(self, lhs: V, rhs: V) -> Option<V> {
+        V::combine(self, lhs, rhs)
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Operator::Add => "+",
+                Operator::Sub => "−",
+                Operator::Mul => "×",
+                Operator::Div => "÷",
+            }
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum ClickOutcome<V = i64> {
+    // First click of a pair.
+    Selected,
+    // Second click of a pair outside quick mode; waits for `GameState::combine`.
+    Pending,
+    // Clicking the selected number again, or a combine that would overflow.
+    Cancelled,
+    Moved { mv: Move<V>, finished: bool },
+}
+
+// The numbers on the board and the pending selection; everything else in `Model` is
+// presentation or bookkeeping around it.
+#[derive(Clone)]
+pub(crate) struct GameState<V = i64> {
+    pub(crate) numbers: Vec<V>,
+    pub(crate) is_used: Vec<bool>,
+    pub(crate) selected: Option<usize>,
+    // `(first, second)` clicked, before the player picks which one survives.
+    pub(crate) pending: Option<(usize, usize)>,
+}
+
+impl<V: Value> GameState<V> {
+    pub(crate) fn new(numbers: Vec<V>) -> Self {
+        Self {
+            is_used: vec![false; numbers.len()],
+            numbers,
+            selected: None,
+            pending: None,
+        }
+    }
+
+    pub(crate) fn clear_selection(&mut self) {
+        self.selected = None;
+        self.pending = None;
+    }
+
+    // Puts `numbers` back on the board with nothing used or selected.
+    pub(crate) fn reset(&mut self, numbers: &[V]) {
+        *self = Self::new(numbers.to_vec());
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.is_used.iter().filter(|&&b| !b).count()
+    }
+
+    // The values still on the board, in id order.
+    pub(crate) fn live_numbers(&self) -> impl Iterator<Item = V> + '_ {
+        self.numbers
+            .iter()
+            .zip(&self.is_used)
+            .filter(|(_, &used)| !used)
+            .map(|(&n, _)| n)
+    }
+
+    // Counted from the flags rather than inferred from the moves made, and exactly one:
+    // a board with nothing left is broken, not won, and has no final number to show.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.remaining() == 1
+    }
+
+    // How far through the game the board is: each move uses up one number, and the game
+    // ends with one left rather than none.
+    pub(crate) fn progress(&self) -> f64 {
+        let total = self.numbers.len();
+        if total <= 1 {
+            return 1.0;
+        }
+        (total - self.remaining()) as f64 / (total - 1) as f64
+    }
+
+    // The last number standing, once the board is down to one.
+    pub(crate) fn final_number(&self) -> Option<V> {
+        if !self.is_finished() {
+            return None;
+        }
+        self.live_numbers().next()
+    }
+
+    // The first click selects `id`; the second combines `operator` into the selected
+    // number and removes `id` from the board.
+    // Only numbers still on the board can be clicked, whatever the input came from.
+    pub(crate) fn is_legal_click(&self, id: usize) -> bool {
+        self.is_used.get(id).is_some_and(|&used| !used)
+    }
+
+    pub(crate) fn apply_click(&mut self, id: usize, operator: Operator) -> ClickOutcome<V> {
+        match self.selected.take() {
+            Some(target) => self.combine(target, id, operator),
+            None => self.select(id),
+        }
+    }
+
+    // Like `apply_click`, but the second click only stages the pair; `combine` then
+    // applies it in whichever direction the player picks.
+    pub(crate) fn stage_click(&mut self, id: usize) -> ClickOutcome<V> {
+        match self.selected.take() {
+            Some(first) if first != id => {
+                self.pending = Some((first, id));
+                ClickOutcome::Pending
+            }
+            Some(_) => ClickOutcome::Cancelled,
+            None => self.select(id),
+        }
+    }
+
+    fn select(&mut self, id: usize) -> ClickOutcome<V> {
+        self.pending = None;
+        self.selected = Some(id);
+        ClickOutcome::Selected
+    }
+
+    // Combines `operator` into `target` and removes `source` from the board.
+    pub(crate) fn combine(
+        &mut self,
+        target: usize,
+        source: usize,
+        operator: Operator,
+    ) -> ClickOutcome<V> {
+        self.clear_selection();
+        let mv = Move {
+            source,
+            target,
+            operator,
+            amount: self.numbers[source],
+            previous: self.numbers[target],
+            elapsed_ms: None,
+        };
+        // A combine that would overflow is dropped like a cancelled selection.
+        if target == source || !self.apply(mv) {
+            return ClickOutcome::Cancelled;
+        }
+        ClickOutcome::Moved {
+            mv,
+            finished: self.is_finished(),
+        }
+    }
+
+    // Returns `false`, leaving the board untouched, if the result overflows.
+    pub(crate) fn apply(&mut self, mv: Move<V>) -> bool {
+        match mv.result() {
+            Some(result) => {
+                self.numbers[mv.target] = result;
+                self.is_used[mv.source] = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn revert(&mut self, mv: Move<V>) {
+        self.numbers[mv.target] = mv.previous;
+        self.is_used[mv.source] = false;
+    }
+
+    // Plays `moves` on a fresh board of `numbers`, or `None` if any of them couldn't have
+    // been played there: an out-of-range or used id, or a recorded value that doesn't match.
+    pub(crate) fn replay(numbers: &[V], moves: &[Move<V>]) -> Option<Self> {
+        let mut game = Self::new(numbers.to_vec());
+        for &mv in moves {
+            let is_live = |id: usize| game.is_used.get(id) == Some(&false);
+            let playable = mv.source != mv.target
+                && is_live(mv.source)
+                && is_live(mv.target)
+                && game.numbers[mv.source] == mv.amount
+                && game.numbers[mv.target] == mv.previous;
+            if !playable || !game.apply(mv) {
+                return None;
+            }
+        }
+        Some(game)
+    }
+}
+
+// The AI opponent's move as `(target, source)`: subtract the largest number left from the
+// smallest. Greedy, with no lookahead, so a careful player can beat it.
+pub(crate) fn ai_next_move(state: &GameState) -> Option<(usize, usize)> {
+    if state.is_finished() {
+        return None;
+    }
+    let live = || (0..state.numbers.len()).filter(|&id| !state.is_used[id]);
+    let target = live().min_by_key(|&id| state.numbers[id])?;
+    let source = live()
+        .filter(|&id| id != target)
+        .max_by_key(|&id| state.numbers[id])?;
+    Some((target, source))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_click_selects() {
+        let mut game = GameState::new(vec![5, 3]);
+        assert_eq!(game.apply_click(0, Operator::Sub), ClickOutcome::Selected);
+        assert_eq!(game.selected, Some(0));
+        assert_eq!(game.numbers, vec![5, 3]);
+    }
+
+    #[test]
+    fn second_click_subtracts_into_the_first() {
+        let mut game = GameState::new(vec![5, 3, 4]);
+        game.apply_click(0, Operator::Sub);
+        let outcome = game.apply_click(1, Operator::Sub);
+
+        assert!(matches!(
+            outcome,
+            ClickOutcome::Moved {
+                finished: false,
+                ..
+            }
+        ));
+        assert_eq!(game.numbers[0], 2);
+        assert_eq!(game.is_used, vec![false, true, false]);
+        assert_eq!(game.selected, None);
+    }
+
+    #[test]
+    fn last_combine_finishes() {
+        let mut game = GameState::new(vec![5, 3]);
+        game.apply_click(1, Operator::Sub);
+        let outcome = game.apply_click(0, Operator::Sub);
+
+        assert!(matches!(
+            outcome,
+            ClickOutcome::Moved { finished: true, .. }
+        ));
+        assert!(game.is_finished());
+        assert_eq!(game.final_number(), Some(-2));
+    }
+
+    #[test]
+    fn boards_of_two_and_three_finish_with_one_left() {
+        let mut game = GameState::new(vec![5, 3]);
+        assert!(!game.is_finished());
+        assert_eq!(game.final_number(), None);
+        game.combine(0, 1, Operator::Sub);
+        assert!(game.is_finished());
+        assert_eq!(game.final_number(), Some(2));
+
+        let mut game = GameState::new(vec![5, 3, 4]);
+        game.combine(0, 1, Operator::Sub);
+        assert!(!game.is_finished());
+        assert_eq!(game.final_number(), None);
+        game.combine(2, 0, Operator::Sub);
+        assert!(game.is_finished());
+        assert_eq!(game.final_number(), Some(2));
+    }
+
+    #[test]
+    fn a_board_with_nothing_left_is_not_finished() {
+        let mut game = GameState::new(vec![5, 3]);
+        game.is_used = vec![true, true];
+        assert!(!game.is_finished());
+        assert_eq!(game.final_number(), None);
+    }
+
+    #[test]
+    fn self_click_cancels_without_a_move() {
+        let mut game = GameState::new(vec![5, 3]);
+        game.apply_click(0, Operator::Sub);
+
+        assert_eq!(game.apply_click(0, Operator::Sub), ClickOutcome::Cancelled);
+        assert_eq!(game.numbers, vec![5, 3]);
+        assert_eq!(game.is_used, vec![false, false]);
+        assert_eq!(game.selected, None);
+        assert!(!game.is_finished());
+    }
+
+    #[test]
+    fn double_click_near_end_game_does_not_finish() {
+        let mut game = GameState::new(vec![5, 3, 4]);
+        game.apply_click(0, Operator::Sub);
+        game.apply_click(1, Operator::Sub);
+
+        game.apply_click(2, Operator::Sub);
+        assert_eq!(game.apply_click(2, Operator::Sub), ClickOutcome::Cancelled);
+        assert_eq!(game.remaining(), 2);
+        assert!(!game.is_finished());
+    }
+
+    #[test]
+    fn staged_pair_combines_in_the_chosen_direction() {
+        let mut game = GameState::new(vec![5, 3, 4]);
+        game.stage_click(0);
+
+        assert_eq!(game.stage_click(1), ClickOutcome::Pending);
+        assert_eq!(game.pending, Some((0, 1)));
+        assert_eq!(game.numbers, vec![5, 3, 4]);
+
+        game.combine(1, 0, Operator::Sub);
+        assert_eq!(game.numbers[1], -2);
+        assert_eq!(game.is_used, vec![true, false, false]);
+        assert_eq!(game.pending, None);
+    }
+
+    #[test]
+    fn overflowing_combine_is_cancelled() {
+        let mut game = GameState::new(vec![i64::MIN, 1]);
+        game.apply_click(0, Operator::Sub);
+
+        assert_eq!(game.apply_click(1, Operator::Sub), ClickOutcome::Cancelled);
+        assert_eq!(game.numbers, vec![i64::MIN, 1]);
+        assert_eq!(game.is_used, vec![false, false]);
+    }
+
+    #[test]
+    fn progress_reaches_one_with_a_single_number_left() {
+        let mut game = GameState::new(vec![5, 3, 4]);
+        assert_eq!(game.progress(), 0.0);
+
+        game.apply_click(0, Operator::Sub);
+        game.apply_click(1, Operator::Sub);
+        assert_eq!(game.progress(), 0.5);
+
+        game.apply_click(0, Operator::Sub);
+        game.apply_click(2, Operator::Sub);
+        assert_eq!(game.progress(), 1.0);
+    }
+
+    #[test]
+    fn replay_reproduces_the_recorded_game() {
+        let mut game = GameState::new(vec![5, 3, 4]);
+        let mut moves = Vec::new();
+        for (first, second) in [(0, 1), (2, 0)] {
+            game.apply_click(first, Operator::Sub);
+            if let ClickOutcome::Moved { mv, .. } = game.apply_click(second, Operator::Sub) {
+                moves.push(mv);
+            }
+        }
+
+        let replayed = GameState::replay(&[5, 3, 4], &moves).unwrap();
+        assert_eq!(replayed.numbers, game.numbers);
+        assert_eq!(replayed.final_number(), Some(2));
+    }
+
+    #[test]
+    fn replay_rejects_tampered_moves() {
+        let mv = Move {
+            source: 1,
+            target: 0,
+            operator: Operator::Sub,
+            amount: 3,
+            previous: 5,
+            elapsed_ms: None,
+        };
+        assert!(GameState::replay(&[5, 3], &[mv]).is_some());
+        // A forged amount, a reused number, and an id off the board.
+        assert!(GameState::replay(&[5, 3], &[Move { amount: -100, ..mv }]).is_none());
+        assert!(GameState::replay(
+            &[5, 3, 4],
+            &[
+                mv,
+                Move {
+                    target: 2,
+                    previous: 4,
+                    ..mv
+                }
+            ]
+        )
+        .is_none());
+        assert!(GameState::replay(&[5, 3], &[Move { source: 7, ..mv }]).is_none());
+    }
+
+    #[test]
+    fn clicks_on_live_numbers_are_legal() {
+        let game = GameState::new(vec![5, 3]);
+        assert!(game.is_legal_click(0));
+        assert!(game.is_legal_click(1));
+    }
+
+    #[test]
+    fn clicks_on_used_or_missing_numbers_are_illegal() {
+        let mut game = GameState::new(vec![5, 3, 4]);
+        game.combine(0, 1, Operator::Sub);
+        assert!(!game.is_legal_click(1));
+        assert!(!game.is_legal_click(3));
+        assert!(!game.is_legal_click(usize::MAX));
+    }
+
+    #[test]
+    fn ai_subtracts_the_largest_from_the_smallest() {
+        let mut game = GameState::new(vec![4, -2, 9]);
+        assert_eq!(ai_next_move(&game), Some((1, 2)));
+
+        game.combine(1, 2, Operator::Sub);
+        game.combine(1, 0, Operator::Sub);
+        assert_eq!(ai_next_move(&game), None);
+        assert_eq!(game.final_number(), Some(-15));
+    }
+
+    #[test]
+    fn revert_undoes_apply() {
+        let mut game = GameState::new(vec![0, 7]);
+        game.apply_click(0, Operator::Mul);
+        if let ClickOutcome::Moved { mv, .. } = game.apply_click(1, Operator::Mul) {
+            game.revert(mv);
+        }
+        assert_eq!(game.numbers, vec![0, 7]);
+        assert_eq!(game.is_used, vec![false, false]);
+    }
+}
@@ -0,0 +1,453 @@
+// UI strings in every supported language.
+
+use seed::window;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub(crate) enum Lang {
+    Ja,
+    En,
+}
+
+impl Lang {
+    // Follows the browser language on first visit; anything but Japanese gets English.
+    pub(crate) fn preferred() -> Self {
+        let language = window().navigator().language().unwrap_or_default();
+        if language.starts_with("ja") {
+            Lang::Ja
+        } else {
+            Lang::En
+        }
+    }
+
+    pub(crate) const fn toggled(self) -> Self {
+        match self {
+            Lang::Ja => Lang::En,
+            Lang::En => Lang::Ja,
+        }
+    }
+
+    pub(crate) const fn t(self, text: Text) -> &'static str {
+        let (ja, en) = text.strings();
+        match self {
+            Lang::Ja => ja,
+            Lang::En => en,
+        }
+    }
+
+    // `t` with each `{}` replaced by the next of `args`.
+    pub(crate) fn format(self, text: Text, args: &[&dyn Display]) -> String {
+        let mut parts = self.t(text).split("{}");
+        let mut out = parts.next().unwrap_or_default().to_owned();
+        for (part, arg) in parts.zip(args) {
+            out.push_str(&arg.to_string());
+            out.push_str(part);
+        }
+        out
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) enum Text {
+    OtherLanguage,
+    Easy,
+    Normal,
+    Hard,
+    RuleRaw,
+    RuleAbs,
+    ScoringRule,
+    DarkTheme,
+    LightTheme,
+    SinglePlayer,
+    Hotseat,
+    TimeAttack,
+    TimeLeft,
+    MoveLimit,
+    MovesLeft,
+    Player1,
+    Player2,
+    Turn,
+    Mute,
+    Unmute,
+    HowToPlay,
+    TutorialSelect,
+    TutorialCombine,
+    TutorialRemove,
+    TutorialScore,
+    GotIt,
+    SavedGameFound,
+    Resume,
+    StartOver,
+    NumberLabel,
+    NumberSelected,
+    NumberHinted,
+    QuickMode,
+    On,
+    Off,
+    ChooseDirection,
+    Undo,
+    Redo,
+    UndoAll,
+    UndosLeft,
+    Reset,
+    NewGame,
+    Hint,
+    SortLayout,
+    ScatterLayout,
+    History,
+    NoMovesYet,
+    HistoryEntry,
+    Moves,
+    Elapsed,
+    PersonalBest,
+    ShowResult,
+    BoardStats,
+    Even,
+    Odd,
+    NumberCount,
+    Seed,
+    SeedPlaceholder,
+    InvalidSeed,
+    KeyLegend,
+    EmptyBoard,
+    FinalScore,
+    DecidedBy,
+    Optimal,
+    ShowOptimal,
+    RegisterPrompt,
+    NamePlaceholder,
+    NameRequired,
+    SubmitRanking,
+    Submitted,
+    Replay,
+    RecentScores,
+    ScoreSummary,
+    CopyResult,
+    Copied,
+    ShareResult,
+    ViewBoard,
+    NoRecords,
+    Rank,
+    Name,
+    Score,
+    FetchRankingFailed,
+    SubmitRankingFailed,
+    Menu,
+    PerfectScore,
+    Settings,
+    ThemeSetting,
+    LanguageSetting,
+    SoundSetting,
+    DifficultySetting,
+    ScoringSetting,
+    Progress,
+    InvalidSubmission,
+    ExportBoard,
+    ImportBoardPlaceholder,
+    InvalidBoardLine,
+    InvalidBoardCount,
+    InvalidBoardValue,
+    CustomBoardUnranked,
+    CardMode,
+    Stats,
+    GamesPlayed,
+    OptimalFinishes,
+    BestScoreStat,
+    BestTime,
+    AverageMoves,
+    ResetStats,
+    ConfirmResetStats,
+    Cancel,
+    StorageUnavailable,
+    Analyze,
+    BestMove,
+    Inaccuracy,
+    Blunder,
+    RationalMode,
+    RationalResult,
+    ResetView,
+    AiOpponent,
+    AiBoard,
+    AiScore,
+    YouWin,
+    AiWins,
+    Draw,
+    MoveDuration,
+    SlowestMove,
+    DailyMode,
+    DailyPuzzle,
+    DailyBest,
+    DailyAlreadySubmitted,
+    ShareChallenge,
+    ChallengeBanner,
+    ChallengeScore,
+    ChallengeBack,
+    Loading,
+    Retry,
+    ReachableBest,
+    OptimalLost,
+    SubtractionOnly,
+    GroupingSetting,
+    SignSetting,
+    NewBest,
+    TiedBest,
+    BehindBest,
+    Pause,
+    Unpause,
+    PausedOverlay,
+    Keypad,
+    NumberList,
+    HighContrastSetting,
+    Sending,
+    ShowUsed,
+    Hud,
+    PhrasePlaceholder,
+    PhraseSeed,
+    DownloadRanking,
+    DownloadFailed,
+    TargetValue,
+    ShowSolution,
+    PlayingSolution,
+    TargetSetting,
+}
+
+impl Text {
+    // `(Japanese, English)`.
+    const fn strings(self) -> (&'static str, &'static str) {
+        match self {
+            Text::OtherLanguage => ("English", "日本語"),
+            Text::Easy => ("かんたん", "Easy"),
+            Text::Normal => ("ふつう", "Normal"),
+            Text::Hard => ("むずかしい", "Hard"),
+            Text::RuleRaw => ("そのまま", "Raw"),
+            Text::RuleAbs => ("絶対値", "Absolute"),
+            Text::ScoringRule => ("スコア: {}", "Scoring: {}"),
+            Text::DarkTheme => ("ダーク", "Dark"),
+            Text::LightTheme => ("ライト", "Light"),
+            Text::SinglePlayer => ("1人で遊ぶ", "1 player"),
+            Text::Hotseat => ("2人で遊ぶ", "2 players"),
+            Text::TimeAttack => ("タイムアタック", "Time attack"),
+            Text::TimeLeft => ("残り {}秒", "{}s left"),
+            Text::MoveLimit => ("手数制限", "Move limit"),
+            Text::MovesLeft => ("残り手数: {}", "Moves left: {}"),
+            Text::Player1 => ("プレイヤー1", "Player 1"),
+            Text::Player2 => ("プレイヤー2", "Player 2"),
+            Text::Turn => ("手番: {}", "Turn: {}"),
+            Text::Mute => ("ミュート", "Mute"),
+            Text::Unmute => ("音を出す", "Unmute"),
+            Text::HowToPlay => ("遊び方", "How to play"),
+            Text::TutorialSelect => (
+                "数字をひとつクリックして選びます。",
+                "Click a number to select it.",
+            ),
+            Text::TutorialCombine => (
+                "別の数字をクリックすると、最初の数字から二つ目の数字が引かれます。",
+                "Click another number to subtract it from the first.",
+            ),
+            Text::TutorialRemove => (
+                "二つ目の数字は盤面から消え、結果が最初の数字の場所に残ります。",
+                "The second number leaves the board and the result takes the first one's place.",
+            ),
+            Text::TutorialScore => (
+                "最後に残った一つの数字があなたのスコアです。",
+                "The last number standing is your score.",
+            ),
+            Text::GotIt => ("わかった", "Got it"),
+            Text::SavedGameFound => (
+                "前回の途中のゲームがあります。",
+                "You have an unfinished game from last time.",
+            ),
+            Text::Resume => ("続きから", "Resume"),
+            Text::StartOver => ("最初から", "Start over"),
+            Text::NumberLabel => ("数字 {}", "Number {}"),
+            Text::NumberSelected => ("数字 {}（選択中）", "Number {}, selected"),
+            Text::NumberHinted => ("数字 {}（ヒント）", "Number {}, suggested by the hint"),
+            Text::QuickMode => ("即時計算: {}", "Quick mode: {}"),
+            Text::On => ("オン", "on"),
+            Text::Off => ("オフ", "off"),
+            Text::ChooseDirection => ("どちらを残す？", "Which one survives?"),
+            Text::Undo => ("一手戻す", "Undo"),
+            Text::Redo => ("やり直し", "Redo"),
+            Text::UndoAll => ("全部戻す", "Undo all"),
+            Text::UndosLeft => ("残りやり直し: {}", "Undos left: {}"),
+            Text::Reset => ("リセット", "Reset"),
+            Text::NewGame => ("新しい問題", "New game"),
+            Text::Hint => ("ヒント", "Hint"),
+            Text::SortLayout => ("整列", "Sort"),
+            Text::ScatterLayout => ("ばらばら", "Scatter"),
+            Text::History => ("履歴", "History"),
+            Text::NoMovesYet => ("まだ手がありません", "No moves yet"),
+            Text::HistoryEntry => ("{} → #{} へ（{}）", "{} → into #{} ({})"),
+            Text::Moves => ("手数: {}", "Moves: {}"),
+            Text::Elapsed => ("経過時間: {}秒", "Time: {}s"),
+            Text::PersonalBest => ("自己ベスト: {}", "Personal best: {}"),
+            Text::ShowResult => ("結果を見る", "Show result"),
+            Text::BoardStats => (
+                "合計: {}（{}） / 最小: {} / 最大: {}",
+                "Sum: {} ({}) / Min: {} / Max: {}",
+            ),
+            Text::Even => ("偶数", "even"),
+            Text::Odd => ("奇数", "odd"),
+            Text::NumberCount => ("数字の数: {}", "Numbers: {}"),
+            Text::Seed => ("シード: {}", "Seed: {}"),
+            Text::SeedPlaceholder => ("シードを入力して Enter", "Type a seed and press Enter"),
+            Text::InvalidSeed => (
+                "シードは0以上の整数で入力してください",
+                "The seed must be a non-negative integer",
+            ),
+            Text::KeyLegend => (
+                "キー操作: Esc で選択解除 / z で一手戻す / ",
+                "Keys: Esc to deselect / z to undo / ",
+            ),
+            Text::EmptyBoard => (
+                "盤面に数字が残っていません。新しい問題を始めてください。",
+                "There are no numbers left on the board. Start a new game.",
+            ),
+            Text::FinalScore => ("最終スコア：{}", "Final score: {}"),
+            Text::DecidedBy => ("（{} の一手で決着）", "(decided by {})"),
+            Text::Optimal => ("理論値: {}", "Optimal: {}"),
+            Text::ShowOptimal => ("理論値を見る", "Show optimal"),
+            Text::RegisterPrompt => (
+                "今回の結果を順位表に登録する↓",
+                "Add this result to the ranking ↓",
+            ),
+            Text::NamePlaceholder => ("ここに名前を入力してね", "Enter your name"),
+            Text::NameRequired => ("名前を入力してください", "Please enter a name"),
+            Text::SubmitRanking => ("順位表に送信", "Submit to ranking"),
+            Text::Submitted => ("送信済み", "Submitted"),
+            Text::Replay => ("リプレイ", "Replay"),
+            Text::RecentScores => ("最近の成績", "Recent scores"),
+            Text::ScoreSummary => ("平均: {} / ベスト: {}", "Average: {} / Best: {}"),
+            Text::CopyResult => ("結果をコピー", "Copy result"),
+            Text::Copied => ("コピーしました", "Copied"),
+            Text::ShareResult => ("Algoで {}点！ 手数 {}", "Scored {} in Algo! {} moves"),
+            Text::ViewBoard => ("盤面を見る", "View board"),
+            Text::NoRecords => ("まだ記録がありません", "No records yet"),
+            Text::Rank => ("順位", "Rank"),
+            Text::Name => ("名前", "Name"),
+            Text::Score => ("スコア", "Score"),
+            Text::FetchRankingFailed => (
+                "順位表を取得できませんでした: {}",
+                "Couldn't load the ranking: {}",
+            ),
+            Text::SubmitRankingFailed => (
+                "順位表に送信できませんでした: {}",
+                "Couldn't submit to the ranking: {}",
+            ),
+            Text::Menu => ("メニュー", "Menu"),
+            Text::PerfectScore => ("🎉 理論値達成！ 🎉", "🎉 Perfect score! 🎉"),
+            Text::Settings => ("設定", "Settings"),
+            Text::ThemeSetting => ("テーマ", "Theme"),
+            Text::LanguageSetting => ("言語", "Language"),
+            Text::SoundSetting => ("サウンド", "Sound"),
+            Text::DifficultySetting => ("難易度", "Difficulty"),
+            Text::ScoringSetting => ("スコア", "Scoring"),
+            Text::Progress => ("進み具合", "Progress"),
+            Text::InvalidSubmission => (
+                "記録された手順がこの盤面と一致しません",
+                "The recorded moves don't match this board",
+            ),
+            Text::ExportBoard => ("盤面をテキストでコピー", "Copy board as text"),
+            Text::ImportBoardPlaceholder => (
+                "「数字 x y」を1行ずつ貼り付けると盤面を読み込みます",
+                "Paste `number x y` lines to load a board",
+            ),
+            Text::InvalidBoardLine => (
+                "{}行目が「数字 x y」の形式になっていません",
+                "Line {} isn't of the form `number x y`",
+            ),
+            Text::InvalidBoardValue => (
+                "{}行目の数字が大きすぎます。絶対値を{}以下にしてください",
+                "The number on line {} is too large; keep it within ±{}",
+            ),
+            Text::InvalidBoardCount => (
+                "数字が{}個あります。{}〜{}個にしてください",
+                "The board has {} numbers; it needs {} to {}",
+            ),
+            Text::CardMode => ("カード表示: {}", "Cards: {}"),
+            Text::Stats => ("統計", "Statistics"),
+            Text::GamesPlayed => ("プレイ回数: {}", "Games played: {}"),
+            Text::OptimalFinishes => (
+                "理論値達成: {} / {}（{}%）",
+                "Optimal finishes: {} / {} ({}%)",
+            ),
+            Text::BestScoreStat => ("ベストスコア: {}", "Best score: {}"),
+            Text::BestTime => ("ベストタイム: {}秒", "Best time: {}s"),
+            Text::AverageMoves => ("平均手数: {}", "Average moves: {}"),
+            Text::ResetStats => ("統計をリセット", "Reset statistics"),
+            Text::ConfirmResetStats => (
+                "統計をすべて消去します。よろしいですか？",
+                "This erases all statistics. Are you sure?",
+            ),
+            Text::Cancel => ("キャンセル", "Cancel"),
+            Text::Analyze => ("分析", "Analyze"),
+            Text::BestMove => ("最善手", "Best move"),
+            Text::Inaccuracy => ("惜しい手", "Inaccuracy"),
+            Text::Blunder => ("悪手", "Blunder"),
+            Text::RationalMode => ("分数モード: {}", "Fractions: {}"),
+            Text::ResetView => ("表示を戻す", "Reset view"),
+            Text::AiOpponent => ("AI対戦: {}", "Versus AI: {}"),
+            Text::AiBoard => ("AIの盤面", "AI's board"),
+            Text::AiScore => ("AIのスコア: {}", "AI's score: {}"),
+            Text::YouWin => ("あなたの勝ち！", "You win!"),
+            Text::AiWins => ("AIの勝ち", "The AI wins"),
+            Text::Draw => ("引き分け", "Draw"),
+            Text::MoveDuration => ("{}秒", "{}s"),
+            Text::SlowestMove => ("最長考", "Slowest"),
+            Text::ShareChallenge => ("チャレンジを共有", "Share challenge"),
+            Text::ChallengeBanner => ("友達からの挑戦！", "A challenge from a friend!"),
+            Text::ChallengeScore => ("相手のスコア: {}", "Their score: {}"),
+            Text::Loading => ("読み込み中…", "Loading…"),
+            Text::Retry => ("再試行", "Retry"),
+            Text::Pause => ("一時停止", "Pause"),
+            Text::Keypad => ("数字パッド", "Keypad"),
+            Text::NumberList => ("数字一覧", "Number list"),
+            Text::HighContrastSetting => ("ハイコントラスト", "High contrast"),
+            Text::Sending => ("送信中...", "Sending..."),
+            Text::Hud => ("ゲーム情報", "Game info"),
+            Text::DownloadRanking => ("順位表をダウンロード:", "Download the ranking:"),
+            Text::ShowSolution => ("最善手順を見る", "Show the optimal line"),
+            Text::PlayingSolution => ("最善手順を再生中", "Playing the optimal line"),
+            Text::TargetValue => ("目標値: {}", "Target: {}"),
+            Text::TargetSetting => ("目標値", "Target value"),
+            Text::DownloadFailed => ("ダウンロードできませんでした", "The download failed"),
+            Text::PhrasePlaceholder => ("合言葉から作る", "Or type a phrase"),
+            Text::PhraseSeed => ("シード: 「{}」 ({})", "Seed: “{}” ({})"),
+            Text::ShowUsed => ("使用済みを表示: {}", "Show used: {}"),
+            Text::Unpause => ("再開", "Resume"),
+            Text::PausedOverlay => ("一時停止中 — クリックで再開", "Paused — click to resume"),
+            Text::NewBest => ("自己ベスト更新！", "New personal best!"),
+            Text::TiedBest => ("自己ベストに並びました", "You matched your personal best"),
+            Text::BehindBest => ("あと {} 点で自己ベスト", "{} short of your personal best"),
+            Text::GroupingSetting => ("桁区切り", "Digit grouping"),
+            Text::SignSetting => ("正の数に+を付ける", "Show + on positives"),
+            Text::ReachableBest => ("到達可能な最善: {}", "Best reachable: {}"),
+            Text::SubtractionOnly => ("（引き算のみ）", " (subtraction only)"),
+            Text::OptimalLost => (
+                "最適解に届かなくなりました",
+                "The optimum is out of reach now",
+            ),
+            Text::ChallengeBack => ("結果を送り返す", "Send your result back"),
+            Text::DailyMode => ("今日のパズル: {}", "Daily puzzle: {}"),
+            Text::DailyPuzzle => ("今日のパズル ({})", "Today's puzzle ({})"),
+            Text::DailyBest => ("今日のベスト: {}", "Today's best: {}"),
+            Text::DailyAlreadySubmitted => (
+                "今日のパズルのスコアは登録済みです",
+                "You've already submitted today's puzzle",
+            ),
+            Text::RationalResult => (
+                "結果: {}（0からの距離 {}）",
+                "Result: {} (distance from zero {})",
+            ),
+            Text::StorageUnavailable => (
+                "保存できない設定です。設定と記録はこのページを閉じると消えます",
+                "Storage is unavailable; settings and records won't survive a reload",
+            ),
+            Text::CustomBoardUnranked => (
+                "読み込んだ盤面は順位表に登録できません",
+                "Imported boards can't be ranked",
+            ),
+        }
+    }
+}
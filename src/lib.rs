@@ -3,20 +3,120 @@
 // but some rules are too "annoying" or are not applicable for your case.)
 #![allow(clippy::wildcard_imports)]
 
-use itertools::izip;
 use rand::prelude::SliceRandom;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 use seed::{prelude::*, *};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
 
+mod format;
+mod game;
+mod i18n;
+mod rational;
+mod storage;
+
+use format::{format_number, FormatOpts};
+use game::{ai_next_move, ClickOutcome, GameState, Move, Operator};
+use i18n::{Lang, Text};
+use rational::Rational;
+
 // ------ ------
 //     Init
 // ------ ------
 
 // `init` describes what should happen when your app started.
-fn init(_: Url, _: &mut impl Orders<Msg>) -> Model {
-    model_default()
+fn init(url: Url, orders: &mut impl Orders<Msg>) -> Model {
+    orders.subscribe(Msg::UrlChanged);
+    // A bare address reopens the page viewed last; a path or a shared board overrides it.
+    let is_bare = url.path().is_empty() && url.search().get(SEED_QUERY).is_none();
+    let page = if is_bare {
+        storage::get(PAGE_KEY).unwrap_or(Page::Play)
+    } else {
+        Page::from_url(&url)
+    };
+    if page == Page::Ranking {
+        orders.send_msg(Msg::FetchRanking);
+    }
+
+    let seed = url
+        .search()
+        .get(SEED_QUERY)
+        .and_then(|values| values.first()?.parse().ok())
+        .unwrap_or_else(new_seed);
+    let challenge = parse_challenge(&url);
+    if page == Page::Play || is_bare {
+        page.url(seed).go_and_replace();
+    }
+
+    orders.stream(streams::window_event(Ev::KeyDown, |event| {
+        // Typing a name, a seed or a board to import must not play the board.
+        let target = event.target()?;
+        let is_editable = target.has_type::<web_sys::HtmlInputElement>()
+            || target.has_type::<web_sys::HtmlTextAreaElement>()
+            || target
+                .dyn_ref::<web_sys::HtmlElement>()
+                .is_some_and(web_sys::HtmlElement::is_content_editable);
+        if is_editable {
+            return None;
+        }
+        Some(Msg::KeyPressed(
+            event.unchecked_into::<web_sys::KeyboardEvent>().key(),
+        ))
+    }));
+    orders.stream(streams::window_event(Ev::Resize, |_| {
+        let (width, height) = viewport_size();
+        Msg::WindowResized(width, height)
+    }));
+
+    // A challenge plays under the sender's settings without changing the saved ones.
+    let difficulty = challenge.as_ref().map_or_else(
+        || storage::get(DIFFICULTY_KEY).unwrap_or(Difficulty::Normal),
+        |challenge| challenge.difficulty,
+    );
+    let scoring_rule = challenge.as_ref().map_or_else(
+        || storage::get(SCORING_RULE_KEY).unwrap_or(ScoringRule::Raw),
+        |challenge| challenge.rule,
+    );
+    let target = challenge.as_ref().map_or_else(
+        || storage::get(TARGET_KEY).map_or(0, clamp_target),
+        |challenge| challenge.target,
+    );
+    let number_count = challenge
+        .as_ref()
+        .and_then(|challenge| challenge.number_count)
+        .unwrap_or_else(|| difficulty.count());
+    // Every game starts outside the timed modes.
+    let category = Category {
+        target,
+        ..Category::default()
+    };
+    let mut model = Model {
+        page,
+        scoring_rule,
+        target,
+        best_score: storage::get(&scoring_rule.best_score_key(category)).ok(),
+        score_history: storage::get(&scoring_rule.score_history_key(category)).unwrap_or_default(),
+        stats: storage::get(&scoring_rule.stats_key(category)).unwrap_or_default(),
+        ranking: load_rankings(scoring_rule, category),
+        theme: storage::get(THEME_KEY).unwrap_or_else(|_| Theme::preferred()),
+        show_tutorial: storage::get::<bool>(TUTORIAL_KEY).is_err(),
+        muted: storage::get(MUTED_KEY).unwrap_or(false),
+        viewport: viewport_size(),
+        lang: storage::get(LANG_KEY).unwrap_or_else(|_| Lang::preferred()),
+        quick_mode: storage::get(QUICK_MODE_KEY).unwrap_or(true),
+        card_mode: storage::get(CARD_MODE_KEY).unwrap_or(false),
+        number_format: storage::get(NUMBER_FORMAT_KEY).unwrap_or_default(),
+        high_contrast: storage::get(HIGH_CONTRAST_KEY).unwrap_or_else(|_| prefers_more_contrast()),
+        saved_game: load_snapshot(),
+        number_count: Some(number_count).filter(|&count| count != difficulty.count()),
+        challenge,
+        ..generate_model(seed, difficulty, number_count)
+    };
+    refresh_reachable(&mut model);
+    warn_if_storage_failed(&model, orders);
+    model
 }
 
 // ------ ------
@@ -25,48 +125,777 @@ fn init(_: Url, _: &mut impl Orders<Msg>) -> Model {
 
 // `Model` describes our app state.
 struct Model {
-    numbers: Vec<i64>,
+    seed: u64,
+    game: GameState,
+    initial_numbers: Vec<i64>,
+    // `(x, y)` in board units (see `board_unit`) from the board's top-left corner: `x`
+    // grows rightwards (`Left`), `y` grows downwards (`Top`).
     points: Vec<(u64, u64)>,
-    is_used: Vec<bool>,
-    selected: Option<usize>,
     page: Page,
     is_finished: bool,
     name: String,
+    history: Vec<Move>,
+    redo_stack: Vec<Move>,
+    best_score: Option<i64>,
+    // Per difficulty, under the active scoring rule.
+    ranking: HashMap<Difficulty, Vec<(String, i64)>>,
+    // Boards fetched from the server since the rule last changed; tabs in here aren't refetched.
+    ranking_fetched: HashSet<Difficulty>,
+    ranking_tab: Difficulty,
+    submitted: bool,
+    send_error: Option<String>,
+    // The submission being sent, kept for retries until the server accepts it.
+    pending_submission: Option<(ScoringRule, RankingSubmission)>,
+    submit_attempts: u32,
+    // The last `SCORE_HISTORY_LEN` finished-game scores under the active rule, oldest first.
+    score_history: Vec<i64>,
+    // Lifetime totals under the active rule, shown on the settings page.
+    stats: Stats,
+    // Whether this game has been counted in `stats`, so finishing again after an undo
+    // doesn't count it twice.
+    stats_recorded: bool,
+    confirm_reset_stats: bool,
+    ranking_status: RankingStatus,
+    // The best score the numbers left can still reach, re-solved after each move rather
+    // than on every render.
+    best_reachable: i64,
+    optimal_reachable: bool,
+    // The best score of the board as dealt; `None` until solved for the current rule.
+    initial_best: Option<i64>,
+    move_count: u32,
+    operator: Operator,
+    start_time: Option<f64>,
+    end_time: Option<f64>,
+    paused: bool,
+    // When the current pause began, and the milliseconds spent in earlier ones; both are
+    // left out of the elapsed time.
+    paused_at: f64,
+    paused_ms: f64,
+    // Re-renders the running clock; dropping the handle stops the ticks.
+    timer: Option<StreamHandle>,
+    // The number whose value just changed and when, for the flash in `view_num`.
+    last_changed: Option<(usize, f64)>,
+    flash_timer: Option<StreamHandle>,
+    theme: Theme,
+    // Suggested `(target, source)` pair, cleared by the next move.
+    hint: Option<(usize, usize)>,
+    show_optimal: bool,
+    // The finished board hit the solver's score. Only worked out once the optimum is shown,
+    // since solving large boards isn't free.
+    was_optimal: bool,
+    difficulty: Difficulty,
+    scoring_rule: ScoringRule,
+    // The value the Abs rule measures distance from.
+    target: i64,
+    hovered: Option<usize>,
+    // The undo button is hovered, so the board previews what undoing would change.
+    undo_preview: bool,
+    // Number of `history` moves re-applied so far while a replay is running.
+    replay_index: usize,
+    replay_timer: Option<StreamHandle>,
+    // The id and time of the last accepted `Msg::Clicked`, to drop touch double-fires.
+    last_click: Option<(usize, f64)>,
+    // The optimal line from `initial_numbers`, solved the first time it's shown.
+    solution: Option<Vec<Move>>,
+    // The running replay plays `solution` rather than `history`.
+    showing_solution: bool,
+    // Undos left in this game; `None` is unlimited.
+    undo_budget: Option<u32>,
+    // Short notice shown at the bottom of the screen until `toast_timer` fires.
+    toast: Option<String>,
+    toast_timer: Option<StreamHandle>,
+    seed_error: Option<String>,
+    // The phrase the current seed was hashed from, if any.
+    seed_phrase: Option<String>,
+    // Set up by `Msg::ImportBoard` rather than `seed`, so it can't be resumed or ranked.
+    is_custom: bool,
+    board_error: Option<String>,
+    layout: Layout,
+    // Hotseat names; `None` is single-player. `current_player` indexes into it.
+    players: Option<[String; 2]>,
+    current_player: u8,
+    show_tutorial: bool,
+    audio: AudioManager,
+    muted: bool,
+    // Window size in CSS pixels; the board is scaled to fit it.
+    viewport: (u64, u64),
+    // The player dismissed the game-over modal to look at the final board.
+    result_closed: bool,
+    name_input: ElRef<web_sys::HtmlInputElement>,
+    close_result_button: ElRef<web_sys::HtmlElement>,
+    // Board view: `zoom` scales the board about its top-left corner, then it's shifted by
+    // `pan` CSS pixels. `(1.0, (0.0, 0.0))` shows the whole board.
+    zoom: f64,
+    pan: (f64, f64),
+    board_frame: ElRef<web_sys::HtmlElement>,
+    lang: Lang,
+    // Combine on the second click; otherwise the player confirms which number survives.
+    quick_mode: bool,
+    // An autosaved game found at startup, until the player resumes or discards it.
+    saved_game: Option<Snapshot>,
+    // What the stored snapshot was last made from, so unchanged boards aren't rewritten.
+    saved_key: Option<SnapshotKey>,
+    // Time attack: seconds allowed from the first move; `None` is untimed.
+    time_limit: Option<f64>,
+    // Move budget: moves allowed before the game ends with whatever is left; `None` is
+    // unlimited.
+    move_limit: Option<u32>,
+    show_history: bool,
+    // The number being dragged, and the one it's currently over.
+    dragging: Option<usize>,
+    drop_target: Option<usize>,
+    show_keypad: bool,
+    show_number_list: bool,
+    hud_collapsed: bool,
+    // Consumed numbers stay on the board as faint ghosts.
+    show_used: bool,
+    // The operator menu from right-clicking a number while another is selected.
+    combine_menu: Option<CombineMenu>,
+    // Where the current touch began, to tell a page swipe from a tap.
+    touch_start: Option<(f64, f64)>,
+    // Overrides the difficulty's count; `None` uses the preset.
+    number_count: Option<usize>,
+    // The compact header's dropdown.
+    menu_open: bool,
+    // Draws each number on a tile coloured by its value instead of as bare text.
+    card_mode: bool,
+    // Colours the history by how good each move was.
+    analyze: bool,
+    // How good each move in `history` was, graded once in `update` rather than per render.
+    // Kept with the move graded, so a move replaced after an undo is graded again.
+    grades: Vec<(Move, Quality)>,
+    // Rational mode's board, played instead of `game` while set.
+    rational: Option<RationalBoard>,
+    number_format: FormatOpts,
+    high_contrast: bool,
+    // The record this game is measured against in the result, taken before it's updated.
+    previous_best: Option<i64>,
+    // Bumped per new board so the entrance animation plays once for it, not on every render.
+    board_generation: u32,
+    // Set when the board came from a friend's challenge link; dropped with the board.
+    challenge: Option<Challenge>,
+    // Races the player on a copy of the same board while set.
+    ai: Option<AiOpponent>,
+    daily: Option<DailyMode>,
+    // A combine waiting for its consumed number to reach the target; the board only changes
+    // once it lands.
+    animating: Option<Animation>,
+    // Re-renders the flight every frame-ish while `animating` is set.
+    animation_timer: Option<StreamHandle>,
 }
 
-fn model_default() -> Model {
-    let mut rng = StdRng::from_seed([13; 32]);
+struct AiOpponent {
+    game: GameState,
+    // Steps the AI; started with the player's first move so neither gets a head start.
+    timer: Option<StreamHandle>,
+}
 
-    let x = [6, 18, 30];
-    let y = [16, 28, 40, 52, 64, 76];
-    let mut points = x
-        .iter()
-        .flat_map(|&i| y.iter().map(move |&j| (i as i64, j as i64)))
-        .map(|(i, j)| (i + rng.gen_range(-2..=2), j + rng.gen_range(-2..=2)))
-        .map(|(i, j)| (i as u64, j as u64))
-        .collect::<Vec<_>>();
-    points.shuffle(&mut rng);
+const AI_STEP_MS: u32 = 1500;
+
+impl AiOpponent {
+    fn new(numbers: &[i64]) -> Self {
+        Self {
+            game: GameState::new(numbers.to_vec()),
+            timer: None,
+        }
+    }
+
+    // Returns whether the AI has moves left.
+    fn step(&mut self) -> bool {
+        match ai_next_move(&self.game) {
+            Some((target, source)) => matches!(
+                self.game.combine(target, source, Operator::Sub),
+                ClickOutcome::Moved {
+                    finished: false,
+                    ..
+                }
+            ),
+            None => false,
+        }
+    }
+
+    // Plays out the rest at once, so the result can be compared as soon as the player ends.
+    fn finish(&mut self) {
+        while self.step() {}
+        self.timer = None;
+    }
+}
+
+// A small board of fractions where `÷` is allowed. It's played for fun only: none of the
+// integer board's records, rankings, saves or helpers apply.
+struct RationalBoard {
+    game: GameState<Rational>,
+    initial_numbers: Vec<Rational>,
+    points: Vec<(u64, u64)>,
+    // The value closest to zero that the board can reach, worked out once it's finished.
+    best: Option<Rational>,
+}
+
+// Exhaustive search grows factorially, so rational boards stay small.
+const RATIONAL_COUNT: usize = 5;
+
+impl RationalBoard {
+    fn generate(seed: u64) -> Self {
+        let mut rng = StdRng::from_seed(seed_bytes(seed));
+        let (xs, ys) = Difficulty::Easy.grid();
+        let points = layout_points(&mut rng, xs, ys, RATIONAL_COUNT);
+        let numbers = (0..RATIONAL_COUNT)
+            .filter_map(|_| {
+                let num = *[-9, -7, -5, -4, -3, -2, -1, 1, 2, 3, 4, 5, 7, 9].choose(&mut rng)?;
+                Rational::new(num, rng.gen_range(1..=6))
+            })
+            .collect::<Vec<_>>();
+        Self {
+            game: GameState::new(numbers.clone()),
+            initial_numbers: numbers,
+            points,
+            best: None,
+        }
+    }
+
+    fn click(&mut self, id: usize, operator: Operator) {
+        let outcome = self.game.apply_click(id, operator);
+        if let ClickOutcome::Moved { finished: true, .. } = outcome {
+            self.best = solve_rational(&self.initial_numbers);
+        }
+    }
+
+    fn reset(&mut self) {
+        self.game.reset(&self.initial_numbers);
+        self.best = None;
+    }
+}
+
+// The reachable final value closest to zero, trying every pair and operator. Boards that
+// are equal up to order share a result, which keeps it fast enough for `RATIONAL_COUNT`.
+fn solve_rational(numbers: &[Rational]) -> Option<Rational> {
+    fn search(
+        numbers: Vec<Rational>,
+        memo: &mut HashMap<Vec<Rational>, Option<Rational>>,
+    ) -> Option<Rational> {
+        if let [n] = numbers[..] {
+            return Some(n);
+        }
+        let mut key = numbers.clone();
+        key.sort();
+        if let Some(&best) = memo.get(&key) {
+            return best;
+        }
+        let operators = [Operator::Add, Operator::Sub, Operator::Mul, Operator::Div];
+        let mut best: Option<Rational> = None;
+        let pairs = (0..numbers.len())
+            .flat_map(|target| (0..numbers.len()).map(move |source| (target, source)))
+            .filter(|(target, source)| target != source);
+        for (target, source) in pairs {
+            for &operator in &operators {
+                let Some(result) = operator.apply(numbers[target], numbers[source]) else {
+                    continue;
+                };
+                let rest = numbers
+                    .iter()
+                    .enumerate()
+                    .filter(|&(i, _)| i != source)
+                    .map(|(i, &n)| if i == target { result } else { n })
+                    .collect();
+                if let Some(value) = search(rest, memo) {
+                    if best.is_none_or(|best| value.abs() < best.abs()) {
+                        best = Some(value);
+                    }
+                }
+            }
+        }
+        memo.insert(key, best);
+        best
+    }
+    search(numbers.to_vec(), &mut HashMap::new())
+}
+
+#[derive(Clone, Copy)]
+struct Animation {
+    // Flies from `mv.source` to `mv.target`.
+    mv: Move,
+    started: f64,
+}
+
+// The part of a game in progress that survives a reload. The board layout is rebuilt
+// from `seed` and `difficulty`, so only the numbers and moves need storing.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    seed: u64,
+    difficulty: Difficulty,
+    number_count: Option<usize>,
+    numbers: Vec<i64>,
+    is_used: Vec<bool>,
+    move_count: u32,
+    history: Vec<Move>,
+    // Undos left; saves from before it was stored get the difficulty's full budget.
+    undo_budget: Option<u32>,
+}
+
+// Changes whenever the snapshot would: a move, undo or redo changes the history's length
+// or last move, and a new game or import the generation.
+#[derive(Clone, Copy, PartialEq)]
+struct SnapshotKey {
+    seed: u64,
+    board_generation: u32,
+    moves: usize,
+    last_move: Option<Move>,
+    is_resumable: bool,
+}
+
+impl SnapshotKey {
+    fn of(model: &Model) -> Self {
+        Self {
+            seed: model.seed,
+            board_generation: model.board_generation,
+            moves: model.history.len(),
+            last_move: model.history.last().copied(),
+            is_resumable: Snapshot::is_resumable(model),
+        }
+    }
+}
+
+impl Snapshot {
+    // Finished, empty or inconsistent games aren't worth resuming.
+    fn is_resumable(model: &Model) -> bool {
+        !(model.is_finished || model.history.is_empty() || is_replaying(model) || model.is_custom)
+    }
+
+    fn of(model: &Model) -> Option<Self> {
+        if !Self::is_resumable(model) {
+            return None;
+        }
+        Some(Self {
+            seed: model.seed,
+            difficulty: model.difficulty,
+            number_count: model.number_count,
+            numbers: model.game.numbers.clone(),
+            is_used: model.game.is_used.clone(),
+            move_count: model.move_count,
+            history: model.history.clone(),
+            undo_budget: model.undo_budget,
+        })
+    }
+
+    // The moves must replay the dealt board into exactly the saved one, as submissions must
+    // (see `verify_submission`).
+    fn is_valid(&self) -> bool {
+        let n = self.number_count.unwrap_or_else(|| self.difficulty.count());
+        if !NUMBER_COUNT_RANGE.contains(&n) || self.is_used.iter().filter(|&&b| !b).count() < 2 {
+            return false;
+        }
+        let initial = generate_model(self.seed, self.difficulty, n).initial_numbers;
+        GameState::replay(&initial, &self.history)
+            .is_some_and(|game| game.numbers == self.numbers && game.is_used == self.is_used)
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Stats {
+    games: u32,
+    optimal_games: u32,
+    best_score: Option<i64>,
+    // Seconds, over games played down to one number.
+    best_time: Option<f64>,
+    total_moves: u64,
+}
+
+impl Stats {
+    fn record(&mut self, score: Option<i64>, optimal: bool, time: Option<f64>, moves: u32) {
+        self.games += 1;
+        self.optimal_games += u32::from(optimal);
+        if let Some(score) = score {
+            self.best_score = Some(self.best_score.map_or(score, |best| best.min(score)));
+        }
+        if let Some(time) = time {
+            self.best_time = Some(self.best_time.map_or(time, |best| best.min(time)));
+        }
+        self.total_moves += u64::from(moves);
+    }
+
+    fn average_moves(&self) -> Option<f64> {
+        (self.games > 0).then(|| self.total_moves as f64 / f64::from(self.games))
+    }
+
+    fn optimal_percent(&self) -> u32 {
+        (self.optimal_games * 100)
+            .checked_div(self.games)
+            .unwrap_or(0)
+    }
+}
+
+// Used until the real window size is known, and wherever it can't be read.
+const DEFAULT_VIEWPORT: (u64, u64) = (1280, 720);
+
+fn viewport_size() -> (u64, u64) {
+    let window = window();
+    let read = |value: Result<JsValue, JsValue>| value.ok()?.as_f64();
+    match (read(window.inner_width()), read(window.inner_height())) {
+        (Some(width), Some(height)) => (width as u64, height as u64),
+        _ => DEFAULT_VIEWPORT,
+    }
+}
 
-    let n = rng.gen_range(12..13);
-    let numbers = (0..n).map(|_| rng.gen_range(-100..=100)).collect::<Vec<_>>();
+// How many numbers a custom board may have; the densest grid holds the maximum.
+const NUMBER_COUNT_RANGE: std::ops::RangeInclusive<usize> = 2..=30;
 
-    points.resize(n, (50, 50));
+// No board can end farther from zero than all of its numbers at their largest added up, so
+// a target beyond that only distorts the Abs score.
+const MAX_TARGET_ABS: i64 = MAX_IMPORTED_ABS * *NUMBER_COUNT_RANGE.end() as i64;
+
+fn clamp_target(target: i64) -> i64 {
+    target.clamp(-MAX_TARGET_ABS, MAX_TARGET_ABS)
+}
+
+// Builds a fresh board of `n` numbers from `seed`; the same seed, difficulty and count
+// always yield the same board.
+pub(crate) fn generate_model(seed: u64, difficulty: Difficulty, n: usize) -> Model {
+    let mut rng = StdRng::from_seed(seed_bytes(seed));
+
+    // A custom count too large for the difficulty's grid borrows a denser preset's, so the
+    // numbers stay on the board instead of spilling into extra rows.
+    let (xs, ys) = std::iter::once(difficulty)
+        .chain(Difficulty::ALL.iter().copied())
+        .map(Difficulty::grid)
+        .find(|(xs, ys)| xs.len() * ys.len() >= n)
+        .unwrap_or_else(|| Difficulty::Hard.grid());
+    let points = layout_points(&mut rng, xs, ys, n);
+
+    let max_abs = difficulty.max_abs();
+    let numbers = (0..n)
+        .map(|_| rng.gen_range(-max_abs..=max_abs))
+        .collect::<Vec<_>>();
 
     Model {
-        numbers,
+        seed,
+        game: GameState::new(numbers.clone()),
+        initial_numbers: numbers,
         points,
-        is_used: vec![false; n],
-        selected: None,
         page: Page::Play,
-        is_finished: true,
+        is_finished: n <= 1,
         name: String::default(),
+        history: Vec::new(),
+        redo_stack: Vec::new(),
+        best_score: None,
+        ranking: HashMap::new(),
+        ranking_fetched: HashSet::new(),
+        ranking_tab: difficulty,
+        submitted: false,
+        send_error: None,
+        pending_submission: None,
+        submit_attempts: 0,
+        score_history: Vec::new(),
+        stats: Stats::default(),
+        stats_recorded: false,
+        confirm_reset_stats: false,
+        ranking_status: RankingStatus::default(),
+        best_reachable: 0,
+        optimal_reachable: true,
+        initial_best: None,
+        move_count: 0,
+        operator: Operator::Sub,
+        start_time: None,
+        end_time: None,
+        paused: false,
+        paused_at: 0.0,
+        paused_ms: 0.0,
+        timer: None,
+        last_changed: None,
+        flash_timer: None,
+        theme: Theme::Light,
+        hint: None,
+        show_optimal: false,
+        was_optimal: false,
+        difficulty,
+        scoring_rule: ScoringRule::Raw,
+        target: 0,
+        hovered: None,
+        undo_preview: false,
+        replay_index: 0,
+        replay_timer: None,
+        last_click: None,
+        solution: None,
+        showing_solution: false,
+        undo_budget: difficulty.undo_budget(),
+        toast: None,
+        toast_timer: None,
+        seed_error: None,
+        seed_phrase: None,
+        is_custom: false,
+        board_error: None,
+        layout: Layout::Scattered,
+        players: None,
+        current_player: 0,
+        show_tutorial: false,
+        audio: AudioManager::default(),
+        muted: false,
+        viewport: DEFAULT_VIEWPORT,
+        result_closed: false,
+        name_input: ElRef::default(),
+        close_result_button: ElRef::default(),
+        zoom: 1.0,
+        pan: (0.0, 0.0),
+        board_frame: ElRef::default(),
+        lang: Lang::Ja,
+        quick_mode: true,
+        saved_game: None,
+        saved_key: None,
+        time_limit: None,
+        move_limit: None,
+        show_history: false,
+        dragging: None,
+        touch_start: None,
+        combine_menu: None,
+        show_keypad: false,
+        show_number_list: false,
+        hud_collapsed: false,
+        show_used: false,
+        drop_target: None,
+        number_count: None,
+        menu_open: false,
+        card_mode: false,
+        analyze: false,
+        grades: Vec::new(),
+        rational: None,
+        board_generation: 0,
+        number_format: FormatOpts::default(),
+        high_contrast: false,
+        previous_best: None,
+        challenge: None,
+        ai: None,
+        daily: None,
+        animating: None,
+        animation_timer: None,
+    }
+}
+
+// Two numbers closer than this on both axes (in board units) would cover each other.
+const MIN_X_GAP: i64 = 7;
+const MIN_Y_GAP: i64 = 5;
+const MAX_JITTER_TRIES: usize = 10;
+
+fn overlaps((x1, y1): (i64, i64), (x2, y2): (i64, i64)) -> bool {
+    (x1 - x2).abs() < MIN_X_GAP && (y1 - y2).abs() < MIN_Y_GAP
+}
+
+// Picks `n` random slots of the `xs` x `ys` grid and jitters each one, re-rolling the
+// jitter when it would overlap an already placed number. The grid grows extra rows when it
+// has fewer slots than numbers.
+fn layout_points(rng: &mut StdRng, xs: &[i64], ys: &[i64], n: usize) -> Vec<(u64, u64)> {
+    let mut ys = ys.to_vec();
+    let y_step = match ys[..] {
+        [.., a, b] => b - a,
+        _ => 12,
+    };
+    while xs.len() * ys.len() < n {
+        ys.push(ys.last().map_or(0, |last| last + y_step));
+    }
+
+    let mut slots = ys
+        .iter()
+        .flat_map(|&y| xs.iter().map(move |&x| (x, y)))
+        .collect::<Vec<_>>();
+    slots.shuffle(rng);
+    slots.truncate(n);
+
+    let mut points: Vec<(i64, i64)> = Vec::with_capacity(n);
+    for (x, y) in slots {
+        let point = (0..MAX_JITTER_TRIES)
+            .map(|_| (x + rng.gen_range(-2..=2), y + rng.gen_range(-2..=2)))
+            .find(|&p| points.iter().all(|&q| !overlaps(p, q)))
+            // Grid slots are spaced wider than the gaps, so the bare slot is always free.
+            .unwrap_or((x, y));
+        points.push(point);
+    }
+    points
+        .into_iter()
+        .map(|(x, y)| (x.max(0) as u64, y.max(0) as u64))
+        .collect()
+}
+
+fn seed_bytes(seed: u64) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    for chunk in bytes.chunks_mut(8) {
+        chunk.copy_from_slice(&seed.to_le_bytes());
+    }
+    bytes
+}
+
+// 64-bit FNV-1a of the trimmed phrase. Spelled out rather than `DefaultHasher`, whose
+// output may change between Rust versions, so a phrase keeps giving the same board.
+fn text_seed(text: &str) -> u64 {
+    text.trim()
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+fn new_seed() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+// The daily puzzle keeps its seed, so starting over replays today's board.
+fn next_seed(model: &Model) -> u64 {
+    model.daily.as_ref().map_or_else(new_seed, DailyMode::seed)
+}
+
+const DAILY_SUBMITTED_KEY: &str = "daily_submitted";
+
+// Today's shared puzzle: the seed comes from the UTC date, so everyone gets the same board.
+struct DailyMode {
+    // `yyyymmdd`.
+    date: u32,
+    // Today's best under the current scoring rule, kept apart from the all-time one.
+    best: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DailyRecord {
+    date: u32,
+    best: i64,
+}
+
+impl DailyMode {
+    fn today(rule: ScoringRule, category: Category) -> Self {
+        let now = js_sys::Date::new_0();
+        let date =
+            now.get_utc_full_year() * 10000 + (now.get_utc_month() + 1) * 100 + now.get_utc_date();
+        Self {
+            date,
+            best: Self::load_best(rule, category, date),
+        }
+    }
+
+    fn load_best(rule: ScoringRule, category: Category, date: u32) -> Option<i64> {
+        storage::get::<DailyRecord>(&rule.daily_best_key(category))
+            .ok()
+            .filter(|record| record.date == date)
+            .map(|record| record.best)
+    }
+
+    fn seed(&self) -> u64 {
+        u64::from(self.date)
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}",
+            self.date / 10000,
+            self.date / 100 % 100,
+            self.date % 100
+        )
+    }
+
+    fn save(&self, rule: ScoringRule, category: Category) {
+        if let Some(best) = self.best {
+            let record = DailyRecord {
+                date: self.date,
+                best,
+            };
+            let _ = storage::set(&rule.daily_best_key(category), &record);
+        }
+    }
+
+    // Only one daily score goes to the ranking per day.
+    fn submitted_today(&self) -> bool {
+        storage::get::<u32>(DAILY_SUBMITTED_KEY).is_ok_and(|date| date == self.date)
     }
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
 enum Page {
     Play,
     Ranking,
+    Settings,
+}
+
+const RANKING_PATH: &str = "ranking";
+const SETTINGS_PATH: &str = "settings";
+const SEED_QUERY: &str = "seed";
+const DIFFICULTY_QUERY: &str = "difficulty";
+const RULE_QUERY: &str = "rule";
+const COUNT_QUERY: &str = "count";
+const TARGET_QUERY: &str = "target";
+// Marks a challenge link; its value is the sender's score, empty if they hadn't finished.
+const CHALLENGE_QUERY: &str = "challenge";
+
+// A board a friend sent, with the settings it has to be played under to compare scores.
+struct Challenge {
+    difficulty: Difficulty,
+    rule: ScoringRule,
+    target: i64,
+    number_count: Option<usize>,
+    score: Option<i64>,
+}
+
+fn parse_challenge(url: &Url) -> Option<Challenge> {
+    let search = url.search();
+    let first = |key: &str| search.get(key)?.first().map(String::as_str);
+    let score = first(CHALLENGE_QUERY)?.parse().ok();
+    Some(Challenge {
+        difficulty: Difficulty::ALL
+            .iter()
+            .copied()
+            .find(|difficulty| Some(difficulty.slug()) == first(DIFFICULTY_QUERY))?,
+        rule: ScoringRule::ALL
+            .iter()
+            .copied()
+            .find(|rule| Some(rule.slug()) == first(RULE_QUERY))?,
+        target: first(TARGET_QUERY)
+            .and_then(|target| target.parse().ok())
+            .map_or(0, clamp_target),
+        number_count: first(COUNT_QUERY)
+            .and_then(|count| count.parse().ok())
+            .filter(|count| NUMBER_COUNT_RANGE.contains(count)),
+        score,
+    })
+}
+
+// A link that reproduces this board and its settings. Once the game is over it carries the
+// score too, so the link doubles as the reply to a challenge.
+fn encode_challenge(model: &Model) -> String {
+    let score = final_score(model)
+        .filter(|_| model.is_finished)
+        .map_or_else(String::new, |score| score.to_string());
+    let mut query = vec![
+        (SEED_QUERY, vec![model.seed.to_string()]),
+        (DIFFICULTY_QUERY, vec![model.difficulty.slug().to_owned()]),
+        (RULE_QUERY, vec![model.scoring_rule.slug().to_owned()]),
+        (CHALLENGE_QUERY, vec![score]),
+    ];
+    if let Some(count) = model.number_count {
+        query.push((COUNT_QUERY, vec![count.to_string()]));
+    }
+    if model.target != 0 {
+        query.push((TARGET_QUERY, vec![model.target.to_string()]));
+    }
+    let origin = window().location().origin().unwrap_or_default();
+    format!("{}{}", origin, Url::new().set_search(UrlSearch::new(query)))
+}
+
+impl Page {
+    // In header order.
+    const ALL: [Page; 3] = [Page::Play, Page::Ranking, Page::Settings];
+
+    fn from_url(url: &Url) -> Self {
+        match url.path().first().map(String::as_str) {
+            Some(RANKING_PATH) => Page::Ranking,
+            Some(SETTINGS_PATH) => Page::Settings,
+            _ => Page::Play,
+        }
+    }
+
+    // The Play URL carries the board's seed so the link reproduces it.
+    fn url(self, seed: u64) -> Url {
+        match self {
+            Page::Play => {
+                Url::new().set_search(UrlSearch::new(vec![(SEED_QUERY, vec![seed.to_string()])]))
+            }
+            Page::Ranking => Url::new().add_path_part(RANKING_PATH),
+            Page::Settings => Url::new().add_path_part(SETTINGS_PATH),
+        }
+    }
 }
 
 impl Display for Page {
@@ -77,218 +906,5057 @@ impl Display for Page {
             match self {
                 Page::Play => "Play",
                 Page::Ranking => "Ranking",
+                Page::Settings => "Settings",
             }
         )
     }
 }
 
-// ------ ------
-//    Update
-// ------ ------
+#[derive(Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
 
-// (Remove the line below once any of your `Msg` variants doesn't implement `Copy`.)
-#[derive(Clone)]
-// `Msg` describes the different events you can modify state with.
-enum Msg {
-    Clicked(usize),
-    ClickedPlay,
-    ClickedRanking,
-    ClickedRollBack,
-    ClickedSendButton,
-    ChangedTextArea(String),
+impl Difficulty {
+    const fn label(self) -> Text {
+        match self {
+            Difficulty::Easy => Text::Easy,
+            Difficulty::Normal => Text::Normal,
+            Difficulty::Hard => Text::Hard,
+        }
+    }
+
+    // Suffix for per-difficulty storage keys and API paths.
+    const fn slug(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        }
+    }
+
+    const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    const fn count(self) -> usize {
+        match self {
+            Difficulty::Easy => 6,
+            Difficulty::Normal => 12,
+            Difficulty::Hard => 18,
+        }
+    }
+
+    // Numbers are drawn from `-max_abs..=max_abs`.
+    const fn max_abs(self) -> i64 {
+        match self {
+            Difficulty::Easy => 20,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 500,
+        }
+    }
+
+    // Harder boards limit undos so they can't be solved by trial and error.
+    const fn undo_budget(self) -> Option<u32> {
+        match self {
+            Difficulty::Easy => None,
+            Difficulty::Normal => Some(5),
+            Difficulty::Hard => Some(3),
+        }
+    }
+
+    // Grid columns (`x`) and rows (`y`) in board units, see `Model::points`. Each grid has more
+    // slots than numbers, and the spacing leaves room for the ±2 jitter.
+    const fn grid(self) -> (&'static [i64], &'static [i64]) {
+        match self {
+            Difficulty::Easy => (&[16, 34, 52, 70], &[10, 26]),
+            Difficulty::Normal => (&[16, 28, 40, 52, 64, 76], &[6, 18, 30]),
+            Difficulty::Hard => (&[6, 16, 26, 36, 46, 56, 66, 76], &[5, 14, 23, 32]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq)]
+enum Layout {
+    // The generated `points`.
+    Scattered,
+    // Remaining numbers in a tidy grid, ascending by value.
+    Sorted,
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+enum ScoringRule {
+    // The remaining number itself.
+    Raw,
+    // Distance of the remaining number from the target, zero unless one is set.
+    Abs,
+}
+
+// Besides the rule, what a score is recorded and ranked under: the Abs rule's target, and
+// time attack and the move budget, whose cut-short boards are scored by their worst number
+// left.
+#[derive(Clone, Copy, Default, PartialEq)]
+struct Category {
+    target: i64,
+    // The time attack's limit in whole seconds.
+    time_limit: Option<u32>,
+    // The budget follows from the board size, so only whether it's on matters.
+    move_limit: bool,
+}
+
+impl Category {
+    fn of(model: &Model) -> Self {
+        Self {
+            target: model.target,
+            time_limit: model.time_limit.map(|limit| limit as u32),
+            move_limit: model.move_limit.is_some(),
+        }
+    }
+}
+
+impl ScoringRule {
+    const ALL: [ScoringRule; 2] = [ScoringRule::Raw, ScoringRule::Abs];
+
+    const fn label(self) -> Text {
+        match self {
+            ScoringRule::Raw => Text::RuleRaw,
+            ScoringRule::Abs => Text::RuleAbs,
+        }
+    }
+
+    const fn slug(self) -> &'static str {
+        match self {
+            ScoringRule::Raw => "raw",
+            ScoringRule::Abs => "abs",
+        }
+    }
+
+    // Raw has no target: lower is simply better.
+    const fn score(self, remaining: i64, target: i64) -> i64 {
+        match self {
+            ScoringRule::Raw => remaining,
+            ScoringRule::Abs => (remaining - target).abs(),
+        }
+    }
+
+    const fn uses_target(self) -> bool {
+        matches!(self, ScoringRule::Abs)
+    }
+
+    // With a target set, in time attack or on a move budget, records are kept apart from
+    // the plain rule's as well.
+    fn with_category(self, key: &str, category: Category) -> String {
+        let mut key = key.to_owned();
+        if self.uses_target() && category.target != 0 {
+            key += &format!("_target_{}", category.target);
+        }
+        if let Some(seconds) = category.time_limit {
+            key += &format!("_ta{}", seconds);
+        }
+        if category.move_limit {
+            key += "_mb";
+        }
+        key
+    }
+
+    const fn toggled(self) -> Self {
+        match self {
+            ScoringRule::Raw => ScoringRule::Abs,
+            ScoringRule::Abs => ScoringRule::Raw,
+        }
+    }
+
+    // Each rule keeps its own records so raw and absolute scores never mix.
+    fn best_score_key(self, category: Category) -> String {
+        let key = match self {
+            ScoringRule::Raw => "best_score",
+            ScoringRule::Abs => "best_score_abs",
+        };
+        self.with_category(key, category)
+    }
+
+    fn score_history_key(self, category: Category) -> String {
+        let key = match self {
+            ScoringRule::Raw => "score_history",
+            ScoringRule::Abs => "score_history_abs",
+        };
+        self.with_category(key, category)
+    }
+
+    fn daily_best_key(self, category: Category) -> String {
+        let key = match self {
+            ScoringRule::Raw => "daily_best",
+            ScoringRule::Abs => "daily_best_abs",
+        };
+        self.with_category(key, category)
+    }
+
+    fn stats_key(self, category: Category) -> String {
+        let key = match self {
+            ScoringRule::Raw => "stats",
+            ScoringRule::Abs => "stats_abs",
+        };
+        self.with_category(key, category)
+    }
+
+    // Rankings are further split by difficulty.
+    fn ranking_key(self, difficulty: Difficulty, category: Category) -> String {
+        let prefix = match self {
+            ScoringRule::Raw => "ranking",
+            ScoringRule::Abs => "ranking_abs",
+        };
+        format!(
+            "{}_{}",
+            self.with_category(prefix, category),
+            difficulty.slug()
+        )
+    }
+
+    fn ranking_path(self, difficulty: Difficulty, category: Category) -> String {
+        let mut prefix = match self {
+            ScoringRule::Raw => "ranking".to_owned(),
+            ScoringRule::Abs if category.target != 0 => {
+                format!("ranking/abs/target/{}", category.target)
+            }
+            ScoringRule::Abs => "ranking/abs".to_owned(),
+        };
+        if let Some(seconds) = category.time_limit {
+            prefix += &format!("/time/{}", seconds);
+        }
+        if category.move_limit {
+            prefix += "/moves";
+        }
+        format!("{}/{}", prefix, difficulty.slug())
+    }
+
+    // The best score reachable from `numbers` under this rule.
+    fn solve(self, numbers: &[i64], target: i64) -> i64 {
+        match self {
+            ScoringRule::Raw => solve_best(numbers),
+            ScoringRule::Abs => solve_best_by(numbers, |n| self.score(n, target)),
+        }
+    }
+
+    // `solve`, along with moves that reach it.
+    fn solve_line(self, numbers: &[i64], target: i64) -> (i64, Vec<Move>) {
+        match best_signs(numbers, |n| self.score(n, target)) {
+            Some(signs) => {
+                let moves = line_for_signs(numbers, &signs);
+                let last = moves.last().and_then(Move::result).unwrap_or(0);
+                (self.score(last, target), moves)
+            }
+            None => (self.solve(numbers, target), Vec::new()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    // Follows the OS setting on first visit.
+    fn preferred() -> Self {
+        let prefers_dark = window()
+            .match_media("(prefers-color-scheme: dark)")
+            .ok()
+            .flatten()
+            .is_some_and(|query| query.matches());
+        if prefers_dark {
+            Theme::Dark
+        } else {
+            Theme::Light
+        }
+    }
+
+    const fn toggled(self) -> Self {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::Light,
+        }
+    }
+
+    const fn background(self) -> &'static str {
+        match self {
+            Theme::Light => "white",
+            Theme::Dark => "#121212",
+        }
+    }
+
+    const fn text_color(self) -> &'static str {
+        match self {
+            Theme::Light => "black",
+            Theme::Dark => "#e0e0e0",
+        }
+    }
+
+    const fn header_background(self) -> &'static str {
+        match self {
+            Theme::Light => "#4385f4",
+            Theme::Dark => "#1f3b70",
+        }
+    }
+
+    // Numbers sit on the blackboard image in both themes.
+    const fn number_color(self) -> &'static str {
+        match self {
+            Theme::Light => "white",
+            Theme::Dark => "lightgray",
+        }
+    }
+
+    const fn selected_color(self) -> &'static str {
+        match self {
+            Theme::Light => "yellow",
+            Theme::Dark => "gold",
+        }
+    }
+
+    const fn result_background(self) -> &'static str {
+        match self {
+            Theme::Light => "#f0f4ff",
+            Theme::Dark => "#1e1e1e",
+        }
+    }
+}
+
+// Asked for by the OS on first visit.
+fn prefers_more_contrast() -> bool {
+    window()
+        .match_media("(prefers-contrast: more)")
+        .ok()
+        .flatten()
+        .is_some_and(|query| query.matches())
+}
+
+// The theme's colors, or in high contrast pure black and white on a flat board with thick
+// outlines. The theme then only picks which of the two is the background.
+#[derive(Clone, Copy)]
+struct Palette {
+    theme: Theme,
+    high_contrast: bool,
+}
+
+impl Palette {
+    const fn of(model: &Model) -> Self {
+        Self {
+            theme: model.theme,
+            high_contrast: model.high_contrast,
+        }
+    }
+
+    const fn number_color(self) -> &'static str {
+        match (self.high_contrast, self.theme) {
+            (false, theme) => theme.number_color(),
+            (true, Theme::Light) => "black",
+            (true, Theme::Dark) => "white",
+        }
+    }
+
+    const fn selected_color(self) -> &'static str {
+        match (self.high_contrast, self.theme) {
+            (false, theme) => theme.selected_color(),
+            (true, Theme::Light) => "blue",
+            (true, Theme::Dark) => "yellow",
+        }
+    }
+
+    // Text drawn on `selected_color`.
+    const fn on_selected_color(self) -> &'static str {
+        match (self.high_contrast, self.theme) {
+            (true, Theme::Dark) => "black",
+            _ => "white",
+        }
+    }
+
+    const fn background(self) -> &'static str {
+        match (self.high_contrast, self.theme) {
+            (false, theme) => theme.background(),
+            (true, Theme::Light) => "white",
+            (true, Theme::Dark) => "black",
+        }
+    }
+
+    const fn header_background(self) -> &'static str {
+        if self.high_contrast {
+            "black"
+        } else {
+            self.theme.header_background()
+        }
+    }
+
+    const fn result_background(self) -> &'static str {
+        match (self.high_contrast, self.theme) {
+            (false, theme) => theme.result_background(),
+            (true, Theme::Light) => "white",
+            (true, Theme::Dark) => "black",
+        }
+    }
+
+    const fn text_color(self) -> &'static str {
+        match (self.high_contrast, self.theme) {
+            (false, theme) => theme.text_color(),
+            (true, Theme::Light) => "black",
+            (true, Theme::Dark) => "white",
+        }
+    }
+
+    // Background and label of a choice button, such as an operator.
+    const fn choice_colors(self, is_selected: bool) -> (&'static str, &'static str) {
+        match (self.high_contrast, is_selected) {
+            (false, true) => ("yellow", "black"),
+            (false, false) => ("white", "black"),
+            (true, true) => (self.selected_color(), self.on_selected_color()),
+            (true, false) => (self.result_background(), self.text_color()),
+        }
+    }
+
+    // Label of a step button; gray is too faint for a disabled one in high contrast.
+    const fn step_color(self, is_enabled: bool) -> &'static str {
+        match (self.high_contrast, is_enabled) {
+            (_, true) => "black",
+            (false, false) => "gray",
+            (true, false) => "#595959",
+        }
+    }
+
+    const fn outline_px(self) -> u32 {
+        if self.high_contrast {
+            5
+        } else {
+            3
+        }
+    }
+
+    // The blackboard image, or a solid fill the numbers stand out against.
+    fn board_surface(self) -> Style {
+        if self.high_contrast {
+            style! {
+                St::Background => self.result_background(),
+                St::Outline => format!("{}px solid {}", self.outline_px(), self.number_color()),
+            }
+        } else {
+            style! {
+                St::BackgroundImage => r##"url("../img/black_board.png")"##,
+                St::BackgroundRepeat => "no-repeat",
+                St::BackgroundPosition => "center top",
+                St::BackgroundSize => "contain",
+            }
+        }
+    }
+}
+
+// Sound effects synthesized with WebAudio, so no audio files have to be served. The context
+// is only created in `unlock`, because browsers keep it suspended until a user interaction;
+// whenever it is unavailable the game simply stays silent.
+#[derive(Default)]
+struct AudioManager {
+    context: Option<web_sys::AudioContext>,
+}
+
+impl AudioManager {
+    // (frequency in Hz, delay in seconds) of each note, all played for `NOTE_SECS`.
+    const CLICK_NOTES: &'static [(f32, f64)] = &[(880.0, 0.0)];
+    const FINISH_NOTES: &'static [(f32, f64)] = &[(523.25, 0.0), (659.25, 0.12), (783.99, 0.24)];
+    const NOTE_SECS: f64 = 0.15;
+    const VOLUME: f32 = 0.2;
+
+    fn unlock(&mut self) {
+        if self.context.is_none() {
+            self.context = web_sys::AudioContext::new().ok();
+        }
+        if let Some(context) = &self.context {
+            // A rejected resume is harmless; the game simply stays silent.
+            let _ = context.resume();
+        }
+    }
+
+    fn play_click(&self, muted: bool) {
+        self.play(Self::CLICK_NOTES, muted);
+    }
+
+    fn play_finish(&self, muted: bool) {
+        self.play(Self::FINISH_NOTES, muted);
+    }
+
+    fn play(&self, notes: &[(f32, f64)], muted: bool) {
+        if muted {
+            return;
+        }
+        if let Some(context) = &self.context {
+            for &(frequency, delay) in notes {
+                let _ = Self::tone(context, frequency, delay);
+            }
+        }
+    }
+
+    fn tone(context: &web_sys::AudioContext, frequency: f32, delay: f64) -> Option<()> {
+        let oscillator = context.create_oscillator().ok()?;
+        let gain = context.create_gain().ok()?;
+        let start = context.current_time() + delay;
+        let end = start + Self::NOTE_SECS;
+        oscillator.frequency().set_value(frequency);
+        // Fade out instead of cutting off, which would click.
+        gain.gain().set_value_at_time(Self::VOLUME, start).ok()?;
+        gain.gain()
+            .exponential_ramp_to_value_at_time(0.001, end)
+            .ok()?;
+        oscillator.connect_with_audio_node(&gain).ok()?;
+        gain.connect_with_audio_node(&context.destination()).ok()?;
+        oscillator.start_with_when(start).ok()?;
+        oscillator.stop_with_when(end).ok()?;
+        Some(())
+    }
+}
+
+// ------ ------
+//    Update
+// ------ ------
+
+// (Remove the line below once any of your `Msg` variants doesn't implement `Copy`.)
+#[derive(Clone)]
+// `Msg` describes the different events you can modify state with.
+enum Msg {
+    Clicked(usize),
+    ClickedRollBack,
+    ClickedRedo,
+    ClickedReset,
+    ClickedNewGame,
+    ClickedSendButton,
+    ChangedTextArea(String),
+    FetchRanking,
+    RankingFetched {
+        rule: ScoringRule,
+        category: Category,
+        difficulty: Difficulty,
+        ranking: Vec<(String, i64)>,
+    },
+    SelectRankingTab(Difficulty),
+    RankingSubmitted,
+    DownloadRanking(RankingFormat),
+    SubmitAttempt,
+    SubmitFailed(String),
+    RankingFailed(String),
+    Tick,
+    SelectOperator(Operator),
+    FlashTick,
+    KeyPressed(String),
+    ToggleTheme,
+    ClickedHint,
+    ShowOptimal,
+    UrlChanged(subs::UrlChanged),
+    SelectDifficulty(Difficulty),
+    ToggleScoringRule,
+    SetTarget(String),
+    Hovered(Option<usize>),
+    ClickedReplay,
+    ShowSolution,
+    ReplayTick,
+    CopyResult,
+    ShowToast(String),
+    ClearToast,
+    LoadSeed(String),
+    SetSeedFromText(String),
+    ClickedUndoAll,
+    UndoPreview(bool),
+    ShareChallenge,
+    ExportBoard,
+    ImportBoard(String),
+    ToggleLayout,
+    ToggleCards,
+    ToggleAnalyze,
+    // `at` is the zoom centre, in CSS pixels from the board frame's corner.
+    Zoom {
+        factor: f64,
+        at: (f64, f64),
+    },
+    Pan(f64, f64),
+    ResetView,
+    ToggleRationalMode,
+    ToggleAiOpponent,
+    ToggleDaily,
+    AiStep,
+    ClickedResetStats,
+    ConfirmResetStats(bool),
+    ToggleHotseat,
+    ShowTutorial,
+    DismissTutorial,
+    ToggleMute,
+    ToggleKeypad,
+    ToggleNumberList,
+    ToggleHud,
+    ToggleShowUsed,
+    TogglePause,
+    ToggleGrouping,
+    ToggleHighContrast,
+    ToggleExplicitSign,
+    WindowResized(u64, u64),
+    ToggleLang,
+    ConfirmCombine(bool),
+    // `at` is the cursor, in client coordinates.
+    OpenCombineMenu {
+        source: usize,
+        at: (f64, f64),
+    },
+    ChooseCombine(Operator),
+    CloseCombineMenu,
+    ResumeSavedGame,
+    ToggleTimeAttack,
+    ToggleMoveLimit,
+    ToggleHistory,
+    ClearSelection,
+    SetNumberCount(usize),
+    ToggleMenu,
+    Navigate(Page),
+    TouchStarted(f64, f64),
+    TouchEnded(f64, f64),
+    AnimationFrame,
+    // Carries `Animation::started` so a stale timeout can't land a newer flight early.
+    AnimationDone(f64),
+    DragStart(usize),
+    DragEntered(usize),
+    DragLeft(usize),
+    DropOn(usize),
+    DragEnd,
+    DiscardSavedGame,
+    ToggleQuickMode,
+    CloseResult,
+    OpenResult,
+}
+
+// `update` describes how to handle each `Msg`.
+fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    apply_msg(msg, model, orders);
+    // Until the player answers the resume prompt, the old save must not be overwritten.
+    if model.saved_game.is_none() {
+        save_snapshot(model);
+    }
+    grade_moves(model);
+    warn_if_storage_failed(model, orders);
+}
+
+fn warn_if_storage_failed(model: &Model, orders: &mut impl Orders<Msg>) {
+    if storage::take_warning() {
+        orders.send_msg(Msg::ShowToast(
+            model.lang.t(Text::StorageUnavailable).to_owned(),
+        ));
+    }
+}
+
+fn apply_msg(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    if matches!(msg, Msg::Clicked(_) | Msg::KeyPressed(_)) {
+        model.audio.unlock();
+    }
+    // Anything that reads or edits the board lands the combine in flight first.
+    if matches!(
+        msg,
+        Msg::Clicked(_)
+            | Msg::KeyPressed(_)
+            | Msg::DragStart(_)
+            | Msg::DropOn(_)
+            | Msg::ConfirmCombine(_)
+            | Msg::ChooseCombine(_)
+            | Msg::ClickedRollBack
+            | Msg::ClickedRedo
+            | Msg::ClickedUndoAll
+            | Msg::ClickedReset
+            | Msg::ClickedHint
+            | Msg::ClickedReplay
+            | Msg::ShowSolution
+            | Msg::ToggleLayout
+    ) {
+        land_animation(model, orders);
+    }
+    match msg {
+        // The board is driven by the replay until it ends.
+        Msg::Clicked(_)
+        | Msg::DragStart(_)
+        | Msg::DropOn(_)
+        | Msg::ConfirmCombine(_)
+        | Msg::OpenCombineMenu { .. }
+        | Msg::ChooseCombine(_)
+        | Msg::ClickedRollBack
+        | Msg::ClickedRedo
+        | Msg::ClickedUndoAll
+            if is_replaying(model) || is_time_up(model) || model.paused => {}
+        // Out of moves, undoing is all that's left.
+        Msg::Clicked(_)
+        | Msg::DragStart(_)
+        | Msg::DropOn(_)
+        | Msg::ConfirmCombine(_)
+        | Msg::OpenCombineMenu { .. }
+        | Msg::ChooseCombine(_)
+        | Msg::ClickedRedo
+            if is_out_of_moves(model) => {}
+        // The board is hidden while paused, so it can't be studied or played either.
+        Msg::KeyPressed(_) | Msg::ClickedHint if model.paused => {}
+        Msg::Clicked(id) => {
+            if accept_click(model, id, js_sys::Date::now()) {
+                click_number(model, id, orders);
+            }
+        }
+        // A drag is the two clicks of the click flow: the dragged number first.
+        Msg::DragStart(id) => {
+            model.game.clear_selection();
+            model.dragging = Some(id);
+            click_number(model, id, orders);
+        }
+        Msg::DragEntered(id) => model.drop_target = Some(id),
+        Msg::DragLeft(id) => {
+            if model.drop_target == Some(id) {
+                model.drop_target = None;
+            }
+        }
+        Msg::DropOn(id) => {
+            model.drop_target = None;
+            if model.dragging.take().is_some() {
+                click_number(model, id, orders);
+            }
+        }
+        // Fires after `DropOn`, so a drag still in progress was dropped off the board.
+        Msg::DragEnd => {
+            model.drop_target = None;
+            if model.dragging.take().is_some() {
+                model.game.clear_selection();
+            }
+        }
+        Msg::ConfirmCombine(keep_first) => {
+            if let Some((first, second)) = model.game.pending {
+                let (target, source) = if keep_first {
+                    (first, second)
+                } else {
+                    (second, first)
+                };
+                let outcome = model.game.combine(target, source, model.operator);
+                handle_click_outcome(model, outcome, orders);
+            }
+        }
+        Msg::OpenCombineMenu { source, at } => {
+            model.combine_menu = model
+                .game
+                .selected
+                .filter(|&target| target != source && model.game.is_legal_click(source))
+                .map(|target| CombineMenu { target, source, at });
+        }
+        Msg::ChooseCombine(operator) => {
+            if let Some(CombineMenu { target, source, .. }) = model.combine_menu.take() {
+                let outcome = model.game.combine(target, source, operator);
+                handle_click_outcome(model, outcome, orders);
+            }
+        }
+        Msg::CloseCombineMenu => model.combine_menu = None,
+        Msg::ResumeSavedGame => {
+            if let Some(snapshot) = model.saved_game.take() {
+                model.number_count = snapshot.number_count;
+                start_new_game(model, snapshot.seed, snapshot.difficulty);
+                model.game.numbers = snapshot.numbers;
+                model.game.is_used = snapshot.is_used;
+                model.move_count = snapshot.move_count;
+                model.current_player = (snapshot.history.len() % 2) as u8;
+                model.history = snapshot.history;
+                // Undos already spent stay spent, but never more than the difficulty allows.
+                let saved_budget = snapshot.undo_budget;
+                model.undo_budget = model
+                    .undo_budget
+                    .map(|full| saved_budget.map_or(full, |left| left.min(full)));
+                refresh_reachable(model);
+            }
+        }
+        Msg::DiscardSavedGame => {
+            model.saved_game = None;
+            let _ = storage::remove(SNAPSHOT_KEY);
+        }
+        Msg::ToggleQuickMode => {
+            model.quick_mode = !model.quick_mode;
+            model.game.clear_selection();
+            let _ = storage::set(QUICK_MODE_KEY, &model.quick_mode);
+        }
+        Msg::UrlChanged(subs::UrlChanged(url)) => {
+            model.menu_open = false;
+            show_page(model, Page::from_url(&url), orders);
+        }
+        Msg::ClickedRollBack => {
+            if roll_back(model) {
+                resume_play(model, orders);
+            }
+            model.game.clear_selection();
+        }
+        Msg::ClickedUndoAll => {
+            if !can_undo_all(model) {
+                return;
+            }
+            let count = model.history.len() as u32;
+            model.undo_budget = model.undo_budget.map(|budget| budget - count);
+            while let Some(mv) = model.history.pop() {
+                revert_move(model, mv);
+            }
+            model.redo_stack.clear();
+            model.game.clear_selection();
+            resume_play(model, orders);
+        }
+        Msg::ClickedRedo => {
+            if let Some(mv) = model.redo_stack.pop() {
+                apply_move(model, mv);
+                check_finished(model, orders);
+            }
+            model.game.clear_selection();
+        }
+        Msg::ClickedReset => {
+            if let Some(board) = &mut model.rational {
+                board.reset();
+                return;
+            }
+            if let Some(ai) = &mut model.ai {
+                *ai = AiOpponent::new(&model.initial_numbers);
+            }
+            // `points` never change during play, so only the numbers need to be restored.
+            model.game.reset(&model.initial_numbers);
+            // Start over as cleanly as a new game does, so the replayed board is recorded,
+            // compared and submitted afresh.
+            model.is_finished = false;
+            model.result_closed = false;
+            model.submitted = false;
+            model.send_error = None;
+            model.pending_submission = None;
+            model.toast = None;
+            model.toast_timer = None;
+            model.stats_recorded = false;
+            model.previous_best = None;
+            model.history.clear();
+            model.redo_stack.clear();
+            model.grades.clear();
+            model.move_count = 0;
+            model.start_time = None;
+            model.end_time = None;
+            model.paused = false;
+            model.paused_ms = 0.0;
+            model.timer = None;
+            model.hint = None;
+            model.show_optimal = false;
+            model.was_optimal = false;
+            model.replay_timer = None;
+            model.solution = None;
+            model.showing_solution = false;
+            refresh_reachable(model);
+            model.undo_budget = model.difficulty.undo_budget();
+            model.current_player = 0;
+        }
+        Msg::ClickedNewGame => start_new_game(model, next_seed(model), model.difficulty),
+        Msg::SelectDifficulty(difficulty) => {
+            model.number_count = None;
+            let _ = storage::set(DIFFICULTY_KEY, &difficulty);
+            start_new_game(model, next_seed(model), difficulty);
+        }
+        Msg::SetNumberCount(count) => {
+            let count = count.clamp(*NUMBER_COUNT_RANGE.start(), *NUMBER_COUNT_RANGE.end());
+            model.number_count = Some(count).filter(|&count| count != model.difficulty.count());
+            start_new_game(model, next_seed(model), model.difficulty);
+        }
+        Msg::Hovered(id) => model.hovered = id,
+        Msg::UndoPreview(shown) => model.undo_preview = shown,
+        Msg::ToggleLang => {
+            model.lang = model.lang.toggled();
+            let _ = storage::set(LANG_KEY, &model.lang);
+        }
+        Msg::CloseResult => model.result_closed = true,
+        Msg::OpenResult => {
+            model.result_closed = false;
+            focus_name_input(model, orders);
+        }
+        Msg::WindowResized(width, height) => {
+            model.viewport = (width, height);
+            clamp_pan(model);
+        }
+        Msg::Zoom { factor, at: (x, y) } => {
+            let zoom = (model.zoom * factor).clamp(1.0, MAX_ZOOM);
+            let scale = zoom / model.zoom;
+            // Keeps the point under the cursor where it is.
+            model.pan = (x - (x - model.pan.0) * scale, y - (y - model.pan.1) * scale);
+            model.zoom = zoom;
+            clamp_pan(model);
+        }
+        Msg::Pan(dx, dy) => {
+            model.pan = (model.pan.0 + dx, model.pan.1 + dy);
+            clamp_pan(model);
+        }
+        Msg::ResetView => {
+            model.zoom = 1.0;
+            model.pan = (0.0, 0.0);
+        }
+        Msg::ToggleMute => {
+            model.muted = !model.muted;
+            let _ = storage::set(MUTED_KEY, &model.muted);
+        }
+        Msg::ToggleGrouping => {
+            model.number_format.group_thousands = !model.number_format.group_thousands;
+            let _ = storage::set(NUMBER_FORMAT_KEY, &model.number_format);
+        }
+        Msg::ToggleHighContrast => {
+            model.high_contrast = !model.high_contrast;
+            let _ = storage::set(HIGH_CONTRAST_KEY, &model.high_contrast);
+        }
+        Msg::ToggleExplicitSign => {
+            model.number_format.explicit_sign = !model.number_format.explicit_sign;
+            let _ = storage::set(NUMBER_FORMAT_KEY, &model.number_format);
+        }
+        Msg::ShowTutorial => model.show_tutorial = true,
+        Msg::DismissTutorial => {
+            model.show_tutorial = false;
+            let _ = storage::set(TUTORIAL_KEY, &true);
+        }
+        Msg::ToggleHotseat => {
+            model.players = match model.players {
+                Some(_) => None,
+                None => Some([
+                    model.lang.t(Text::Player1).to_owned(),
+                    model.lang.t(Text::Player2).to_owned(),
+                ]),
+            };
+        }
+        Msg::ClickedResetStats => model.confirm_reset_stats = true,
+        Msg::ConfirmResetStats(reset) => {
+            model.confirm_reset_stats = false;
+            if reset {
+                model.stats = Stats::default();
+                let _ = storage::remove(&model.scoring_rule.stats_key(Category::of(model)));
+            }
+        }
+        Msg::ToggleAnalyze => model.analyze = !model.analyze,
+        Msg::ToggleDaily => {
+            model.daily = match model.daily {
+                Some(_) => None,
+                None => Some(DailyMode::today(model.scoring_rule, Category::of(model))),
+            };
+            start_new_game(model, next_seed(model), model.difficulty);
+        }
+        Msg::ToggleAiOpponent => {
+            model.ai = match model.ai {
+                Some(_) => None,
+                None => Some(AiOpponent::new(&model.initial_numbers)),
+            };
+            start_new_game(model, next_seed(model), model.difficulty);
+        }
+        Msg::AiStep => {
+            if let Some(ai) = &mut model.ai {
+                if !ai.step() {
+                    ai.timer = None;
+                }
+            }
+        }
+        Msg::ToggleRationalMode => {
+            model.rational = match model.rational {
+                Some(_) => {
+                    if model.operator == Operator::Div {
+                        model.operator = Operator::Sub;
+                    }
+                    None
+                }
+                None => Some(RationalBoard::generate(model.seed)),
+            };
+            start_new_game(model, next_seed(model), model.difficulty);
+        }
+        Msg::ToggleCards => {
+            model.card_mode = !model.card_mode;
+            let _ = storage::set(CARD_MODE_KEY, &model.card_mode);
+        }
+        Msg::ToggleLayout => {
+            model.layout = match model.layout {
+                Layout::Scattered => Layout::Sorted,
+                Layout::Sorted => Layout::Scattered,
+            }
+        }
+        Msg::ClickedReplay => {
+            model.showing_solution = false;
+            start_replay(model, orders);
+        }
+        Msg::ShowSolution => {
+            if model.solution.is_none() {
+                let (rule, target) = (model.scoring_rule, model.target);
+                model.solution = Some(rule.solve_line(&model.initial_numbers, target).1);
+            }
+            model.showing_solution = true;
+            start_replay(model, orders);
+        }
+        Msg::CopyResult => {
+            if let Some(score) = final_score(model) {
+                let text = model
+                    .lang
+                    .format(Text::ShareResult, &[&score, &model.move_count]);
+                let copied = model.lang.t(Text::Copied).to_owned();
+                orders.perform_cmd(async move {
+                    copy_to_clipboard(&text)
+                        .await
+                        .then_some(Msg::ShowToast(copied))
+                });
+            }
+        }
+        Msg::ShowToast(text) => {
+            model.toast = Some(text);
+            model.toast_timer = Some(
+                orders.stream_with_handle(streams::interval(TOAST_DURATION_MS, || Msg::ClearToast)),
+            );
+        }
+        Msg::SetSeedFromText(text) => {
+            if text.trim().is_empty() {
+                return;
+            }
+            start_new_game(model, text_seed(&text), model.difficulty);
+            model.seed_phrase = Some(text.trim().to_owned());
+        }
+        Msg::LoadSeed(input) => match input.trim().parse() {
+            Ok(seed) => start_new_game(model, seed, model.difficulty),
+            Err(_) => model.seed_error = Some(model.lang.t(Text::InvalidSeed).to_owned()),
+        },
+        Msg::ShareChallenge => {
+            let text = encode_challenge(model);
+            let copied = model.lang.t(Text::Copied).to_owned();
+            orders.perform_cmd(async move {
+                copy_to_clipboard(&text)
+                    .await
+                    .then_some(Msg::ShowToast(copied))
+            });
+        }
+        Msg::ExportBoard => {
+            let text = board_to_text(model);
+            let copied = model.lang.t(Text::Copied).to_owned();
+            orders.perform_cmd(async move {
+                copy_to_clipboard(&text)
+                    .await
+                    .then_some(Msg::ShowToast(copied))
+            });
+        }
+        Msg::ImportBoard(text) => match parse_board(&text) {
+            Ok(board) => {
+                start_new_game(model, next_seed(model), model.difficulty);
+                let (numbers, points): (Vec<_>, Vec<_>) = board.into_iter().unzip();
+                model.game = GameState::new(numbers.clone());
+                model.initial_numbers = numbers;
+                model.initial_best = None;
+                model.points = points;
+                model.is_custom = true;
+                if model.move_limit.is_some() {
+                    model.move_limit = Some(move_budget(model.game.numbers.len()));
+                }
+                refresh_reachable(model);
+                if let Some(ai) = &mut model.ai {
+                    *ai = AiOpponent::new(&model.initial_numbers);
+                }
+            }
+            Err(err) => {
+                model.board_error = Some(match err {
+                    BoardTextError::Line(line) => {
+                        model.lang.format(Text::InvalidBoardLine, &[&line])
+                    }
+                    BoardTextError::Value(line) => model
+                        .lang
+                        .format(Text::InvalidBoardValue, &[&line, &MAX_IMPORTED_ABS]),
+                    BoardTextError::Count(count) => model.lang.format(
+                        Text::InvalidBoardCount,
+                        &[&count, NUMBER_COUNT_RANGE.start(), NUMBER_COUNT_RANGE.end()],
+                    ),
+                })
+            }
+        },
+        Msg::ClearToast => {
+            model.toast = None;
+            model.toast_timer = None;
+        }
+        Msg::ReplayTick => {
+            let moves = if model.showing_solution {
+                model.solution.as_deref().unwrap_or_default()
+            } else {
+                &model.history
+            };
+            match moves.get(model.replay_index).copied() {
+                Some(mv) => {
+                    model.game.apply(mv);
+                    model.replay_index += 1;
+                    flash(model, mv.target, orders);
+                }
+                None => {
+                    model.replay_timer = None;
+                    // Back to the player's own board for the result.
+                    if model.showing_solution {
+                        model.showing_solution = false;
+                        model.game.reset(&model.initial_numbers);
+                        for &mv in &model.history {
+                            model.game.apply(mv);
+                        }
+                    }
+                }
+            }
+        }
+        Msg::ToggleScoringRule => {
+            model.scoring_rule = model.scoring_rule.toggled();
+            let _ = storage::set(SCORING_RULE_KEY, &model.scoring_rule);
+            reload_records(model, orders);
+        }
+        Msg::SetTarget(input) => {
+            // The field snaps back to the current target on anything unparsable, and to the
+            // nearest allowed one on anything out of range.
+            if let Ok(target) = input.trim().parse() {
+                let target = clamp_target(target);
+                model.target = target;
+                let _ = storage::set(TARGET_KEY, &target);
+                reload_records(model, orders);
+            }
+        }
+        Msg::ClickedSendButton => {
+            if model.submitted || model.pending_submission.is_some() {
+                return;
+            }
+            let score = match final_score(model) {
+                Some(score) if model.is_finished => score,
+                _ => return,
+            };
+            let name = model.name.trim().to_owned();
+            if name.is_empty() {
+                model.send_error = Some(model.lang.t(Text::NameRequired).to_owned());
+                return;
+            }
+            if model.is_custom {
+                model.send_error = Some(model.lang.t(Text::CustomBoardUnranked).to_owned());
+                return;
+            }
+            if model.daily.as_ref().is_some_and(DailyMode::submitted_today) {
+                model.send_error = Some(model.lang.t(Text::DailyAlreadySubmitted).to_owned());
+                return;
+            }
+            let (rule, difficulty) = (model.scoring_rule, model.difficulty);
+            let number_count = model.game.numbers.len();
+            // The server would reject it anyway; say so before the round trip.
+            let target = model.target;
+            if verify_submission(
+                model.seed,
+                difficulty,
+                number_count,
+                (rule, target),
+                &model.history,
+            ) != Some(score)
+            {
+                model.send_error = Some(model.lang.t(Text::InvalidSubmission).to_owned());
+                return;
+            }
+            let submission = RankingSubmission {
+                name,
+                score,
+                difficulty,
+                time_limit: model.time_limit,
+                move_limit: model.move_limit,
+                target,
+                seed: model.seed,
+                number_count,
+                numbers: model.initial_numbers.clone(),
+                moves: model.history.clone(),
+            };
+            model.pending_submission = Some((rule, submission));
+            model.submit_attempts = 0;
+            model.send_error = None;
+            orders.send_msg(Msg::SubmitAttempt);
+        }
+        Msg::SubmitAttempt => {
+            if let Some((rule, submission)) = &model.pending_submission {
+                model.submit_attempts += 1;
+                orders.perform_cmd(submit_ranking(*rule, model.lang, submission.clone()));
+            }
+        }
+        Msg::SubmitFailed(err) => {
+            if model.pending_submission.is_none() {
+                return;
+            }
+            if model.submit_attempts < MAX_SUBMIT_ATTEMPTS {
+                let delay = SUBMIT_RETRY_BASE_MS << (model.submit_attempts - 1);
+                orders.perform_cmd(cmds::timeout(delay, || Msg::SubmitAttempt));
+            } else {
+                model.pending_submission = None;
+                model.send_error = Some(err);
+            }
+        }
+        Msg::ChangedTextArea(str) => model.name = sanitize_name(&str),
+        Msg::FetchRanking => {
+            model.ranking_status = RankingStatus::Loading;
+            orders.perform_cmd(fetch_ranking(
+                model.scoring_rule,
+                Category::of(model),
+                model.ranking_tab,
+                model.lang,
+            ));
+        }
+        Msg::SelectRankingTab(difficulty) => {
+            model.ranking_tab = difficulty;
+            if !model.ranking_fetched.contains(&difficulty) {
+                orders.send_msg(Msg::FetchRanking);
+            }
+        }
+        Msg::RankingFetched {
+            rule,
+            category,
+            difficulty,
+            ranking,
+        } => {
+            // Filed under the board it was fetched for, which the player may have left since.
+            let _ = storage::set(&rule.ranking_key(difficulty, category), &ranking);
+            if (rule, category) != (model.scoring_rule, Category::of(model)) {
+                return;
+            }
+            model.ranking.insert(difficulty, ranking);
+            model.ranking_fetched.insert(difficulty);
+            // A reply for a tab the player has since left doesn't end the current fetch.
+            if difficulty == model.ranking_tab {
+                model.ranking_status = RankingStatus::Loaded;
+            }
+        }
+        Msg::RankingSubmitted => {
+            // `None` if a new game was started while it was in flight.
+            let Some((rule, submission)) = model.pending_submission.take() else {
+                orders.send_msg(Msg::FetchRanking);
+                return;
+            };
+            let (difficulty, category) = (submission.difficulty, submission.category());
+            let entries = model.ranking.entry(difficulty).or_default();
+            entries.push((submission.name, submission.score));
+            let _ = storage::set(&rule.ranking_key(difficulty, category), entries);
+            if let Some(daily) = &model.daily {
+                let _ = storage::set(DAILY_SUBMITTED_KEY, &daily.date);
+            }
+            model.name.clear();
+            model.submitted = true;
+            model.ranking_tab = difficulty;
+            if model.page == Page::Ranking {
+                orders.send_msg(Msg::FetchRanking);
+            } else {
+                navigate(model, Page::Ranking, orders);
+            }
+        }
+        Msg::DownloadRanking(format) => {
+            let entries = sorted_ranking(model);
+            let (extension, mime, contents) = match format {
+                RankingFormat::Csv => ("csv", "text/csv", ranking_to_csv(&entries)),
+                RankingFormat::Json => ("json", "application/json", ranking_to_json(&entries)),
+            };
+            let file_name = format!(
+                "ranking-{}-{}.{}",
+                model.ranking_tab.slug(),
+                model.scoring_rule.slug(),
+                extension
+            );
+            if download(&file_name, mime, &contents).is_none() {
+                orders.send_msg(Msg::ShowToast(
+                    model.lang.t(Text::DownloadFailed).to_owned(),
+                ));
+            }
+        }
+        Msg::RankingFailed(err) => model.ranking_status = RankingStatus::Error(err),
+        Msg::TogglePause => {
+            if model.paused {
+                model.paused = false;
+                if model.start_time.is_some() {
+                    model.paused_ms += js_sys::Date::now() - model.paused_at;
+                    start_timer(model, orders);
+                    if let Some(ai) = model.ai.as_mut().filter(|ai| !ai.game.is_finished()) {
+                        ai.timer = Some(
+                            orders
+                                .stream_with_handle(streams::interval(AI_STEP_MS, || Msg::AiStep)),
+                        );
+                    }
+                }
+            } else if !model.is_finished {
+                land_animation(model, orders);
+                model.paused = true;
+                model.paused_at = js_sys::Date::now();
+                model.game.clear_selection();
+                model.timer = None;
+                if let Some(ai) = &mut model.ai {
+                    ai.timer = None;
+                }
+            }
+        }
+        Msg::Tick => {
+            if is_time_up(model) && !model.is_finished {
+                finish_game(model, orders);
+            }
+        }
+        Msg::ToggleHistory => model.show_history = !model.show_history,
+        Msg::ToggleKeypad => model.show_keypad = !model.show_keypad,
+        Msg::ToggleNumberList => model.show_number_list = !model.show_number_list,
+        Msg::ToggleHud => model.hud_collapsed = !model.hud_collapsed,
+        Msg::ToggleShowUsed => model.show_used = !model.show_used,
+        Msg::ToggleMenu => model.menu_open = !model.menu_open,
+        Msg::Navigate(page) => {
+            model.menu_open = false;
+            navigate(model, page, orders);
+        }
+        Msg::TouchStarted(x, y) => model.touch_start = Some((x, y)),
+        Msg::TouchEnded(x, y) => {
+            if let Some(page) = model
+                .touch_start
+                .take()
+                .and_then(|start| swipe_target(model.page, start, (x, y)))
+            {
+                model.menu_open = false;
+                navigate(model, page, orders);
+            }
+        }
+        Msg::AnimationFrame => {}
+        Msg::AnimationDone(started) => {
+            if model
+                .animating
+                .is_some_and(|animation| animation.started == started)
+            {
+                land_animation(model, orders);
+            }
+        }
+        Msg::ClearSelection => {
+            model.game.clear_selection();
+            model.combine_menu = None;
+            if let Some(board) = &mut model.rational {
+                board.game.clear_selection();
+            }
+            model.hovered = None;
+        }
+        Msg::ToggleTimeAttack => {
+            model.time_limit = match model.time_limit {
+                Some(_) => None,
+                None => Some(TIME_ATTACK_SECONDS),
+            };
+            start_new_game(model, next_seed(model), model.difficulty);
+            reload_records(model, orders);
+        }
+        Msg::ToggleMoveLimit => {
+            // Sized for the board in `start_new_game`.
+            model.move_limit = match model.move_limit {
+                Some(_) => None,
+                None => Some(0),
+            };
+            start_new_game(model, next_seed(model), model.difficulty);
+            reload_records(model, orders);
+        }
+        Msg::SelectOperator(operator) => model.operator = operator,
+        Msg::KeyPressed(key) => {
+            // Key labels index the integer board.
+            if model.page != Page::Play || model.rational.is_some() {
+                return;
+            }
+            match key.as_str() {
+                "Escape" => model.game.clear_selection(),
+                "z" => {
+                    orders.send_msg(Msg::ClickedRollBack);
+                }
+                _ => {
+                    let id = key
+                        .chars()
+                        .next()
+                        .filter(|_| key.chars().count() == 1)
+                        .and_then(|c| KEY_LABELS.iter().position(|&label| label == c))
+                        .and_then(|i| visible_ids(model).nth(i));
+                    if let Some(id) = id {
+                        orders.send_msg(Msg::Clicked(id));
+                    }
+                }
+            }
+        }
+        Msg::ToggleTheme => {
+            model.theme = model.theme.toggled();
+            let _ = storage::set(THEME_KEY, &model.theme);
+        }
+        Msg::ClickedHint if subtraction_only(model) => model.hint = suggest_move(model),
+        Msg::ClickedHint => {}
+        Msg::ShowOptimal => {
+            model.show_optimal = true;
+            model.was_optimal = reached_optimal(model);
+        }
+        Msg::FlashTick => {
+            if flashing_id(model).is_none() {
+                model.last_changed = None;
+                model.flash_timer = None;
+            }
+        }
+    }
+}
+
+// The records kept for the active rule and category, after either changes.
+fn reload_records(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    let (rule, category) = (model.scoring_rule, Category::of(model));
+    model.solution = None;
+    model.grades.clear();
+    model.initial_best = None;
+    model.best_score = storage::get(&rule.best_score_key(category)).ok();
+    model.score_history = storage::get(&rule.score_history_key(category)).unwrap_or_default();
+    model.stats = storage::get(&rule.stats_key(category)).unwrap_or_default();
+    refresh_reachable(model);
+    if let Some(daily) = &mut model.daily {
+        daily.best = DailyMode::load_best(rule, category, daily.date);
+    }
+    model.ranking = load_rankings(rule, category);
+    model.ranking_fetched.clear();
+    if model.page == Page::Ranking {
+        orders.send_msg(Msg::FetchRanking);
+    }
+}
+
+// The locally cached boards for every difficulty under `rule`.
+fn load_rankings(rule: ScoringRule, category: Category) -> HashMap<Difficulty, Vec<(String, i64)>> {
+    Difficulty::ALL
+        .iter()
+        .filter_map(|&difficulty| {
+            let entries = storage::get(&rule.ranking_key(difficulty, category)).ok()?;
+            Some((difficulty, entries))
+        })
+        .collect()
+}
+
+fn load_snapshot() -> Option<Snapshot> {
+    match storage::get::<Snapshot>(SNAPSHOT_KEY) {
+        Ok(snapshot) if snapshot.is_valid() => Some(snapshot),
+        // Missing, unreadable or stale: make sure it doesn't linger.
+        _ => {
+            let _ = storage::remove(SNAPSHOT_KEY);
+            None
+        }
+    }
+}
+
+// Only touches storage when the board changed since the last save, as `update` runs for
+// every tick and animation frame.
+fn save_snapshot(model: &mut Model) {
+    let key = SnapshotKey::of(model);
+    if model.saved_key == Some(key) {
+        return;
+    }
+    model.saved_key = Some(key);
+    match Snapshot::of(model) {
+        Some(snapshot) => {
+            let _ = storage::set(SNAPSHOT_KEY, &snapshot);
+        }
+        None => {
+            let _ = storage::remove(SNAPSHOT_KEY);
+        }
+    }
+}
+
+struct CombineMenu {
+    target: usize,
+    source: usize,
+    at: (f64, f64),
+}
+
+// Some touch screens report one tap as two clicks, which would select and deselect (or
+// stage and cancel) in one go. Anything slower than this is taken as meant.
+const DOUBLE_FIRE_MS: f64 = 120.0;
+
+// `false` for a repeat click on the same number within `DOUBLE_FIRE_MS` of the last one.
+fn accept_click(model: &mut Model, id: usize, now: f64) -> bool {
+    let repeated = model
+        .last_click
+        .is_some_and(|(last, at)| last == id && now - at < DOUBLE_FIRE_MS);
+    if !repeated {
+        model.last_click = Some((id, now));
+    }
+    !repeated
+}
+
+// Every input path (board, keyboard, keypad, drag) ends up here, so illegal ids are
+// dropped here rather than trusted to the view.
+fn click_number(model: &mut Model, id: usize, orders: &mut impl Orders<Msg>) {
+    if let Some(board) = &mut model.rational {
+        if board.game.is_legal_click(id) {
+            board.click(id, model.operator);
+        }
+        return;
+    }
+    if !model.game.is_legal_click(id) {
+        return;
+    }
+    let outcome = if model.quick_mode {
+        model.game.apply_click(id, model.operator)
+    } else {
+        model.game.stage_click(id)
+    };
+    handle_click_outcome(model, outcome, orders);
+}
+
+fn handle_click_outcome(model: &mut Model, outcome: ClickOutcome, orders: &mut impl Orders<Msg>) {
+    match outcome {
+        ClickOutcome::Selected | ClickOutcome::Pending => {}
+        ClickOutcome::Cancelled => model.hovered = None,
+        ClickOutcome::Moved { mv, .. } => {
+            if model.start_time.is_none() {
+                model.start_time = Some(js_sys::Date::now());
+                start_timer(model, orders);
+                if let Some(ai) = &mut model.ai {
+                    ai.timer = Some(
+                        orders.stream_with_handle(streams::interval(AI_STEP_MS, || Msg::AiStep)),
+                    );
+                }
+            }
+            model.audio.play_click(model.muted);
+            model.hovered = None;
+            // Held back until `land_animation`, so the number is still on the board in flight.
+            model.game.revert(mv);
+            start_animation(model, mv, orders);
+        }
+    }
+}
+
+const ANIMATION_DURATION_MS: u32 = 250;
+
+fn start_animation(model: &mut Model, mv: Move, orders: &mut impl Orders<Msg>) {
+    let started = js_sys::Date::now();
+    model.animating = Some(Animation { mv, started });
+    model.animation_timer =
+        Some(orders.stream_with_handle(streams::interval(16, || Msg::AnimationFrame)));
+    orders.perform_cmd(cmds::timeout(ANIMATION_DURATION_MS, move || {
+        Msg::AnimationDone(started)
+    }));
+}
+
+// Applies the combine in flight, if any, and does the bookkeeping for it.
+fn land_animation(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    let Some(Animation { mv, .. }) = model.animating.take() else {
+        return;
+    };
+    model.animation_timer = None;
+    if !model.game.apply(mv) {
+        return;
+    }
+    let could_reach_optimal = model.optimal_reachable;
+    record_move(model, mv);
+    model.redo_stack.clear();
+    flash(model, mv.target, orders);
+    if could_reach_optimal && !model.optimal_reachable && subtraction_only(model) {
+        orders.send_msg(Msg::ShowToast(model.lang.t(Text::OptimalLost).to_owned()));
+    }
+    // Only a real combine can change how many numbers are left.
+    check_finished(model, orders);
+}
+
+// Replaces the board while keeping the player's preferences and records.
+fn start_new_game(model: &mut Model, seed: u64, difficulty: Difficulty) {
+    let with_ai = model.ai.is_some();
+    *model = Model {
+        page: model.page,
+        best_score: model.best_score,
+        score_history: std::mem::take(&mut model.score_history),
+        stats: std::mem::take(&mut model.stats),
+        operator: model.operator,
+        theme: model.theme,
+        scoring_rule: model.scoring_rule,
+        target: model.target,
+        layout: model.layout,
+        players: model.players.take(),
+        show_tutorial: model.show_tutorial,
+        lang: model.lang,
+        quick_mode: model.quick_mode,
+        card_mode: model.card_mode,
+        number_format: model.number_format,
+        high_contrast: model.high_contrast,
+        analyze: model.analyze,
+        time_limit: model.time_limit,
+        move_limit: model.move_limit,
+        show_history: model.show_history,
+        show_keypad: model.show_keypad,
+        show_number_list: model.show_number_list,
+        hud_collapsed: model.hud_collapsed,
+        show_used: model.show_used,
+        audio: std::mem::take(&mut model.audio),
+        muted: model.muted,
+        viewport: model.viewport,
+        ranking: std::mem::take(&mut model.ranking),
+        ranking_fetched: std::mem::take(&mut model.ranking_fetched),
+        ranking_status: std::mem::take(&mut model.ranking_status),
+        ranking_tab: model.ranking_tab,
+        number_count: model.number_count,
+        daily: model.daily.take(),
+        board_generation: model.board_generation.wrapping_add(1),
+        rational: model
+            .rational
+            .as_ref()
+            .map(|_| RationalBoard::generate(seed)),
+        ..generate_model(
+            seed,
+            difficulty,
+            model.number_count.unwrap_or_else(|| difficulty.count()),
+        )
+    };
+    if model.move_limit.is_some() {
+        model.move_limit = Some(move_budget(model.game.numbers.len()));
+    }
+    if with_ai {
+        model.ai = Some(AiOpponent::new(&model.initial_numbers));
+    }
+    refresh_reachable(model);
+    if model.page == Page::Play {
+        Page::Play.url(seed).go_and_replace();
+    }
+}
+
+// Shorter swipes are taps or scrolls.
+const SWIPE_MIN_DISTANCE: f64 = 60.0;
+
+// The page a swipe from `start` to `end` leads to, in header order: leftwards goes to the
+// next page, rightwards to the previous. Mostly-vertical moves are scrolls, not swipes.
+fn swipe_target(page: Page, start: (f64, f64), end: (f64, f64)) -> Option<Page> {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    if dx.abs() < SWIPE_MIN_DISTANCE || dx.abs() < 2.0 * dy.abs() {
+        return None;
+    }
+    let index = Page::ALL.iter().position(|&p| p == page)?;
+    let index = if dx < 0.0 {
+        index + 1
+    } else {
+        index.checked_sub(1)?
+    };
+    Page::ALL.get(index).copied()
+}
+
+// Switches page and records it in the browser history so back/forward work.
+fn navigate(model: &mut Model, page: Page, orders: &mut impl Orders<Msg>) {
+    if model.page != page {
+        page.url(model.seed).go_and_push();
+    }
+    show_page(model, page, orders);
+}
+
+fn show_page(model: &mut Model, page: Page, orders: &mut impl Orders<Msg>) {
+    model.page = page;
+    let _ = storage::set(PAGE_KEY, &page);
+    if page == Page::Ranking {
+        orders.send_msg(Msg::FetchRanking);
+    }
+}
+
+fn apply_move(model: &mut Model, mv: Move) {
+    if model.game.apply(mv) {
+        record_move(model, mv);
+    }
+}
+
+// Bookkeeping for a move already applied to `model.game`.
+fn record_move(model: &mut Model, mv: Move) {
+    let elapsed_ms = model.start_time.map(|_| elapsed_ms(model).max(0.0) as u32);
+    model.history.push(Move { elapsed_ms, ..mv });
+    model.move_count += 1;
+    model.hint = None;
+    model.current_player = 1 - model.current_player;
+    refresh_reachable(model);
+}
+
+// Call whenever the numbers on the board change outside a move.
+fn refresh_reachable(model: &mut Model) {
+    let live = model.game.live_numbers().collect::<Vec<_>>();
+    let (rule, target) = (model.scoring_rule, model.target);
+    model.best_reachable = reachable_best(&live, rule, target);
+    // The dealt board only changes with the rule or the board itself, which clear it.
+    let initial_best = initial_best(model);
+    model.initial_best = Some(initial_best);
+    model.optimal_reachable = model.best_reachable == initial_best;
+}
+
+// The solvers only know subtraction, so the optimum, the hint and the move grades no
+// longer describe a game once another operator has been used.
+fn subtraction_only(model: &Model) -> bool {
+    model.history.iter().all(|mv| mv.operator == Operator::Sub)
+}
+
+// The best score `remaining` can still be brought to.
+fn reachable_best(remaining: &[i64], rule: ScoringRule, target: i64) -> i64 {
+    rule.solve(remaining, target)
+}
+
+// The optimum of the board as dealt, cached by `refresh_reachable`.
+fn initial_best(model: &Model) -> i64 {
+    model
+        .initial_best
+        .unwrap_or_else(|| reachable_best(&model.initial_numbers, model.scoring_rule, model.target))
+}
+
+// The lowest final value reachable by subtracting numbers into one another.
+//
+// No search is needed: every subtraction tree evaluates to `Σ ±x` with at least one `+`
+// (the leftmost leaf) and one `−`, and conversely any such sign choice is reachable —
+// subtract the other `+` terms into one `−` term, the other `−` terms into one `+` term,
+// then the former into the latter. So the optimum gives positives `−` and negatives `+`
+// (`−Σ|x|`), unless every number has the same strict sign, in which case the smallest
+// magnitude must take the other sign.
+fn solve_best(numbers: &[i64]) -> i64 {
+    match numbers {
+        [] => 0,
+        [n] => *n,
+        _ => {
+            let abs_sum = numbers.iter().map(|n| n.abs()).sum::<i64>();
+            let same_sign = numbers.iter().all(|&n| n > 0) || numbers.iter().all(|&n| n < 0);
+            if same_sign {
+                let min_abs = numbers.iter().map(|n| n.abs()).min().unwrap_or(0);
+                -abs_sum + 2 * min_abs
+            } else {
+                -abs_sum
+            }
+        }
+    }
+}
+
+// Every final value reachable by subtracting numbers into one another: the sums `Σ ±x`
+// with at least one `+` and one `−` (see `solve_best`).
+fn reachable_finals(numbers: &[i64]) -> HashSet<i64> {
+    match numbers {
+        [] => HashSet::from([0]),
+        [n] => HashSet::from([*n]),
+        _ => {
+            // `(sum, has_plus, has_minus)`
+            let mut states = HashSet::from([(0, false, false)]);
+            for &n in numbers {
+                states = states
+                    .iter()
+                    .flat_map(|&(sum, plus, minus)| [(sum + n, true, minus), (sum - n, plus, true)])
+                    .collect();
+            }
+            states
+                .into_iter()
+                .filter(|&(_, plus, minus)| plus && minus)
+                .map(|(sum, _, _)| sum)
+                .collect()
+        }
+    }
+}
+
+// The lowest `score` over every reachable final value, for rules with no closed form.
+fn solve_best_by(numbers: &[i64], score: impl Fn(i64) -> i64) -> i64 {
+    reachable_finals(numbers)
+        .into_iter()
+        .map(score)
+        .min()
+        .unwrap_or(0)
+}
+
+type SignState = (i64, bool, bool);
+
+// The signs, `true` for `+`, of a reachable final value with the lowest `score`; `None` for
+// boards of fewer than two numbers, which need no moves. Walks the same states as
+// `reachable_finals`, remembering how each was first reached.
+fn best_signs(numbers: &[i64], score: impl Fn(i64) -> i64) -> Option<Vec<bool>> {
+    if numbers.len() < 2 {
+        return None;
+    }
+    let mut layers: Vec<HashMap<SignState, (SignState, bool)>> = Vec::new();
+    for &n in numbers {
+        let previous = layers.last().map_or_else(
+            || vec![(0, false, false)],
+            |layer| layer.keys().copied().collect(),
+        );
+        let mut layer = HashMap::new();
+        for state @ (sum, plus, minus) in previous {
+            layer.entry((sum + n, true, minus)).or_insert((state, true));
+            layer.entry((sum - n, plus, true)).or_insert((state, false));
+        }
+        layers.push(layer);
+    }
+    let mut state = layers
+        .last()?
+        .keys()
+        .copied()
+        .filter(|&(_, plus, minus)| plus && minus)
+        .min_by_key(|&(sum, _, _)| (score(sum), sum))?;
+    let mut signs = vec![false; numbers.len()];
+    for (sign, layer) in signs.iter_mut().zip(&layers).rev() {
+        let (previous, plus) = layer[&state];
+        *sign = plus;
+        state = previous;
+    }
+    Some(signs)
+}
+
+// Subtractions that evaluate to `Σ ±x` for `signs` (see `solve_best`): the other `+` terms
+// into the first `−` term, the other `−` terms into the first `+` term, then the former into
+// the latter.
+fn line_for_signs(numbers: &[i64], signs: &[bool]) -> Vec<Move> {
+    let (plus, minus) = match (
+        signs.iter().position(|&sign| sign),
+        signs.iter().position(|&sign| !sign),
+    ) {
+        (Some(plus), Some(minus)) => (plus, minus),
+        _ => return Vec::new(),
+    };
+    let mut values = numbers.to_vec();
+    let mut moves = Vec::new();
+    let mut subtract = |source: usize, target: usize| {
+        let mv = Move {
+            source,
+            target,
+            operator: Operator::Sub,
+            amount: values[source],
+            previous: values[target],
+            elapsed_ms: None,
+        };
+        values[target] = mv.previous - mv.amount;
+        moves.push(mv);
+    };
+    for (id, &sign) in signs.iter().enumerate() {
+        if id != plus && id != minus {
+            subtract(id, if sign { minus } else { plus });
+        }
+    }
+    subtract(minus, plus);
+    moves
+}
+
+#[derive(Clone, Copy)]
+enum Quality {
+    // Keeps the best score reachable.
+    Best,
+    // Gives up a little of it.
+    Inaccuracy,
+    Blunder,
+}
+
+impl Quality {
+    const fn color(self) -> &'static str {
+        match self {
+            Quality::Best => "limegreen",
+            Quality::Inaccuracy => "gold",
+            Quality::Blunder => "tomato",
+        }
+    }
+
+    const fn label(self) -> Text {
+        match self {
+            Quality::Best => Text::BestMove,
+            Quality::Inaccuracy => Text::Inaccuracy,
+            Quality::Blunder => Text::Blunder,
+        }
+    }
+}
+
+// Grades the moves of `history` not yet in `grades`, while the analysis is shown.
+fn grade_moves(model: &mut Model) {
+    if !model.analyze {
+        return;
+    }
+    let valid = model
+        .grades
+        .iter()
+        .zip(&model.history)
+        .take_while(|((graded, _), mv)| graded == *mv)
+        .count();
+    model.grades.truncate(valid);
+    // Only the subtraction moves before the first other operator can be judged.
+    let gradable = model
+        .history
+        .iter()
+        .take_while(|mv| mv.operator == Operator::Sub)
+        .count();
+    if valid >= gradable {
+        return;
+    }
+    // Replays the game so each move is judged on the board it was made on.
+    let (rule, target) = (model.scoring_rule, model.target);
+    let mut game = GameState::new(model.initial_numbers.clone());
+    for (i, &mv) in model.history.iter().enumerate().take(gradable) {
+        if i >= valid {
+            let quality = classify_move(rule, target, &game, mv);
+            model.grades.push((mv, quality));
+        }
+        game.apply(mv);
+    }
+}
+
+// How much of the best reachable score `mv` gives up, judged by solving the board before
+// and after it. Losing up to a tenth of the optimum counts as an inaccuracy.
+fn classify_move(rule: ScoringRule, target: i64, before: &GameState, mv: Move) -> Quality {
+    let best = |game: &GameState| rule.solve(&game.live_numbers().collect::<Vec<_>>(), target);
+    let best_before = best(before);
+    let mut after = before.clone();
+    if !after.apply(mv) {
+        return Quality::Blunder;
+    }
+    let loss = best(&after) - best_before;
+    if loss <= 0 {
+        Quality::Best
+    } else if loss <= (best_before.abs() / 10).max(1) {
+        Quality::Inaccuracy
+    } else {
+        Quality::Blunder
+    }
+}
+
+fn revert_move(model: &mut Model, mv: Move) {
+    model.game.revert(mv);
+    model.move_count -= 1;
+    model.current_player = 1 - model.current_player;
+    refresh_reachable(model);
+}
+
+// Whose turn it is, in hotseat mode.
+fn current_player_name(model: &Model) -> Option<&str> {
+    let players = model.players.as_ref()?;
+    Some(&players[usize::from(model.current_player)])
+}
+
+// The player who made the final move and so owns the score, in hotseat mode.
+fn last_mover_name(model: &Model) -> Option<&str> {
+    let players = model.players.as_ref()?;
+    model.history.last()?;
+    Some(&players[usize::from(1 - model.current_player)])
+}
+
+// Undoes the last move, if allowed. `false` if there was nothing to undo.
+fn roll_back(model: &mut Model) -> bool {
+    if !can_undo(model) {
+        return false;
+    }
+    let Some(mv) = model.history.pop() else {
+        return false;
+    };
+    model.undo_budget = model.undo_budget.map(|budget| budget - 1);
+    revert_move(model, mv);
+    model.redo_stack.push(mv);
+    leave_finished_state(model);
+    true
+}
+
+// Leaves the finished state after moves were taken back: dismisses the result and whatever
+// came with it, so the board is playable again. The modal and banner unmount with
+// `is_finished`.
+fn leave_finished_state(model: &mut Model) {
+    if !model.is_finished {
+        return;
+    }
+    model.is_finished = false;
+    model.result_closed = false;
+    model.was_optimal = false;
+    model.submitted = false;
+    model.send_error = None;
+    model.pending_submission = None;
+    model.toast = None;
+    model.toast_timer = None;
+}
+
+fn resume_play(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    leave_finished_state(model);
+    model.hint = None;
+    if model.end_time.take().is_some() {
+        start_timer(model, orders);
+    }
+}
+
+// Sum, min and max of the numbers still on the board; all zero for an empty board.
+fn board_stats(model: &Model) -> (i64, i64, i64) {
+    let remaining = || visible_ids(model).map(|id| model.game.numbers[id]);
+    (
+        remaining().sum(),
+        remaining().min().unwrap_or(0),
+        remaining().max().unwrap_or(0),
+    )
+}
+
+// Greedy hint: the `(target, source)` pair whose combine leaves the remaining total
+// closest to zero.
+fn suggest_move(model: &Model) -> Option<(usize, usize)> {
+    let total = visible_ids(model)
+        .map(|id| i128::from(model.game.numbers[id]))
+        .sum::<i128>();
+    visible_ids(model)
+        .flat_map(|target| visible_ids(model).map(move |source| (target, source)))
+        .filter(|(target, source)| target != source)
+        .filter_map(|(target, source)| {
+            let (a, b) = (model.game.numbers[target], model.game.numbers[source]);
+            let result = model.operator.apply(a, b)?;
+            let after = total - i128::from(a) - i128::from(b) + i128::from(result);
+            Some(((target, source), after.abs()))
+        })
+        .min_by_key(|&(_, after)| after)
+        .map(|(pair, _)| pair)
+}
+
+const THEME_KEY: &str = "theme";
+// Present once the tutorial has been dismissed.
+const TUTORIAL_KEY: &str = "tutorial_seen";
+const MUTED_KEY: &str = "muted";
+const NUMBER_FORMAT_KEY: &str = "number_format";
+const HIGH_CONTRAST_KEY: &str = "high_contrast";
+const LANG_KEY: &str = "lang";
+const QUICK_MODE_KEY: &str = "quick_mode";
+const CARD_MODE_KEY: &str = "card_mode";
+const SNAPSHOT_KEY: &str = "saved_game";
+const DIFFICULTY_KEY: &str = "difficulty";
+const SCORING_RULE_KEY: &str = "scoring_rule";
+const PAGE_KEY: &str = "page";
+const TARGET_KEY: &str = "target";
+
+// Keys that pick the n-th number still on the board; `z` is reserved for undo.
+const KEY_LABELS: &[char] = &[
+    '1', '2', '3', '4', '5', '6', '7', '8', '9', '0', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i',
+    'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y',
+];
+
+fn visible_ids(model: &Model) -> impl Iterator<Item = usize> + '_ {
+    model
+        .game
+        .is_used
+        .iter()
+        .enumerate()
+        .filter(|(_, &b)| !b)
+        .map(|(id, _)| id)
+}
+
+fn can_undo(model: &Model) -> bool {
+    !model.history.is_empty() && model.undo_budget != Some(0) && !is_time_up(model)
+}
+
+// Undoing everything costs one undo per move, so the budget has to cover the whole history.
+fn can_undo_all(model: &Model) -> bool {
+    can_undo(model)
+        && model
+            .undo_budget
+            .is_none_or(|budget| budget as usize >= model.history.len())
+}
+
+const TIME_ATTACK_SECONDS: f64 = 60.0;
+
+fn is_time_up(model: &Model) -> bool {
+    model
+        .time_limit
+        .is_some_and(|limit| elapsed_seconds(model) >= limit)
+}
+
+// Half the moves a full game takes, so the board has to be planned down to its worst
+// number rather than played out.
+fn move_budget(number_count: usize) -> u32 {
+    (number_count / 2).max(1) as u32
+}
+
+// Like running out of time, the worst number left is scored (see `board_score`).
+fn is_out_of_moves(model: &Model) -> bool {
+    model
+        .move_limit
+        .is_some_and(|limit| model.move_count >= limit)
+}
+
+fn remaining_moves(model: &Model) -> Option<u32> {
+    let limit = model.move_limit?;
+    Some(limit.saturating_sub(model.move_count))
+}
+
+fn remaining_seconds(model: &Model) -> Option<f64> {
+    let limit = model.time_limit?;
+    Some((limit - elapsed_seconds(model)).max(0.0))
+}
+
+const REPLAY_INTERVAL_MS: u32 = 700;
+
+// Plays `history`, or `solution` if it's being shown, from the initial board.
+fn start_replay(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    model.game.reset(&model.initial_numbers);
+    model.replay_index = 0;
+    model.replay_timer =
+        Some(orders.stream_with_handle(streams::interval(REPLAY_INTERVAL_MS, || Msg::ReplayTick)));
+}
+
+const fn is_replaying(model: &Model) -> bool {
+    model.replay_timer.is_some()
+}
+
+const FLASH_DURATION_MS: f64 = 400.0;
+
+fn flash(model: &mut Model, id: usize, orders: &mut impl Orders<Msg>) {
+    model.last_changed = Some((id, js_sys::Date::now()));
+    model.flash_timer = Some(orders.stream_with_handle(streams::interval(50, || Msg::FlashTick)));
+}
+
+// The number still within its flash window, if any.
+fn flashing_id(model: &Model) -> Option<usize> {
+    model
+        .last_changed
+        .filter(|&(_, at)| js_sys::Date::now() - at < FLASH_DURATION_MS)
+        .map(|(id, _)| id)
+}
+
+fn start_timer(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    model.timer = Some(orders.stream_with_handle(streams::interval(100, || Msg::Tick)));
+}
+
+fn check_finished(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    if model.game.is_finished() || is_out_of_moves(model) {
+        if !model.is_finished {
+            finish_game(model, orders);
+        }
+        model.is_finished = true;
+    }
+}
+
+// Ends the game, whether the board is down to one number or time ran out.
+fn finish_game(model: &mut Model, orders: &mut impl Orders<Msg>) {
+    model.is_finished = true;
+    model.result_closed = false;
+    focus_name_input(model, orders);
+    if model.start_time.is_some() {
+        model.end_time = Some(js_sys::Date::now());
+    }
+    model.timer = None;
+    if let Some(ai) = &mut model.ai {
+        ai.finish();
+    }
+    let optimal = reached_optimal(model);
+    model.was_optimal = model.show_optimal && optimal;
+    if record_result(model, optimal) {
+        save_records(model);
+    }
+    model.audio.play_finish(model.muted);
+}
+
+// Counts the game in the best score, the score history and the stats. Only on the first
+// finish: after an undo the records already hold this game, so `false` and nothing changes.
+fn record_result(model: &mut Model, optimal: bool) -> bool {
+    if model.stats_recorded {
+        return false;
+    }
+    model.previous_best = match &model.daily {
+        Some(daily) => daily.best,
+        None => model.best_score,
+    };
+    let score = final_score(model);
+    if let Some(score) = score {
+        let best = match &mut model.daily {
+            Some(daily) => &mut daily.best,
+            None => &mut model.best_score,
+        };
+        if best.is_none_or(|best| score < best) {
+            *best = Some(score);
+        }
+        record_score_history(model, score);
+    }
+    // A time-up board has no finishing time to compare.
+    let time =
+        (model.game.is_finished() && model.start_time.is_some()).then(|| elapsed_seconds(model));
+    model.stats.record(score, optimal, time, model.move_count);
+    model.stats_recorded = true;
+    true
+}
+
+// Persists what `record_result` counted; a failed write only costs them across reloads.
+fn save_records(model: &Model) {
+    let (rule, category) = (model.scoring_rule, Category::of(model));
+    match &model.daily {
+        Some(daily) => daily.save(rule, category),
+        None => {
+            if let Some(best) = model.best_score {
+                let _ = storage::set(&rule.best_score_key(category), &best);
+            }
+        }
+    }
+    let _ = storage::set(&rule.score_history_key(category), &model.score_history);
+    let _ = storage::set(&rule.stats_key(category), &model.stats);
+}
+
+// Running out of time never counts, even if the worst number left happens to match.
+fn reached_optimal(model: &Model) -> bool {
+    model.game.is_finished()
+        && subtraction_only(model)
+        && final_score(model) == Some(initial_best(model))
+}
+
+// Focuses the result form once the modal is on screen.
+fn focus_name_input(model: &Model, orders: &mut impl Orders<Msg>) {
+    let input = model.name_input.clone();
+    orders.after_next_render(move |_| {
+        if let Some(input) = input.get() {
+            let _ = input.focus();
+        }
+    });
+}
+
+// The score of the last number left on the board under the active rule; lower is better.
+fn final_score(model: &Model) -> Option<i64> {
+    board_score(model.scoring_rule, model.target, &model.game)
+}
+
+fn board_score(rule: ScoringRule, target: i64, game: &GameState) -> Option<i64> {
+    if game.is_finished() {
+        game.final_number().map(|n| rule.score(n, target))
+    } else {
+        // Time ran out with several numbers left: the worst of them counts.
+        game.live_numbers().map(|n| rule.score(n, target)).max()
+    }
+}
+
+// The score `moves` really reach from the generated board, so a submission is never
+// trusted on its own word. The server runs the same replay; `None` means the log is invalid.
+fn verify_submission(
+    seed: u64,
+    difficulty: Difficulty,
+    number_count: usize,
+    (rule, target): (ScoringRule, i64),
+    moves: &[Move],
+) -> Option<i64> {
+    let numbers = generate_model(seed, difficulty, number_count).initial_numbers;
+    board_score(rule, target, &GameState::replay(&numbers, moves)?)
+}
+
+const MAX_NAME_LEN: usize = 20;
+
+// Drops control characters and caps the length in characters; the ends are only trimmed
+// on submit so a space can still be typed between words.
+fn sanitize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_NAME_LEN)
+        .collect()
+}
+
+const SCORE_HISTORY_LEN: usize = 20;
+
+fn record_score_history(model: &mut Model, score: i64) {
+    model.score_history.push(score);
+    let excess = model.score_history.len().saturating_sub(SCORE_HISTORY_LEN);
+    model.score_history.drain(..excess);
+}
+
+const TOAST_DURATION_MS: u32 = 2000;
+
+// One `number x y` line per number still on the board, in `Model::points` coordinates.
+// `parse_board` reads the same format back.
+fn board_to_text(model: &Model) -> String {
+    let mut text = String::from("# number x y\n");
+    for id in visible_ids(model) {
+        let (x, y) = model.points[id];
+        text.push_str(&format!("{} {} {}\n", model.game.numbers[id], x, y));
+    }
+    text
+}
+
+// A number and where it sits on the board.
+type PlacedNumber = (i64, (u64, u64));
+
+enum BoardTextError {
+    // 1-based.
+    Line(usize),
+    // A number beyond `MAX_IMPORTED_ABS`, on this 1-based line.
+    Value(usize),
+    Count(usize),
+}
+
+// As large as generated boards get. Bigger values would overflow the solver's sums, and a
+// wide spread makes the exhaustive one for the Abs rule too slow to run on every move.
+const MAX_IMPORTED_ABS: i64 = Difficulty::Hard.max_abs();
+
+// Blank lines and `#` comments are skipped; positions must lie on the board.
+fn parse_board(text: &str) -> Result<Vec<PlacedNumber>, BoardTextError> {
+    let mut board = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parse = || {
+            let mut fields = line.split_whitespace();
+            let number = fields.next()?.parse().ok()?;
+            let x = fields.next()?.parse().ok().filter(|&x: &u64| x < 100)?;
+            let y = fields.next()?.parse().ok().filter(|&y| y < BOARD_HEIGHT)?;
+            fields.next().is_none().then_some((number, (x, y)))
+        };
+        let placed: PlacedNumber = parse().ok_or(BoardTextError::Line(i + 1))?;
+        if !(-MAX_IMPORTED_ABS..=MAX_IMPORTED_ABS).contains(&placed.0) {
+            return Err(BoardTextError::Value(i + 1));
+        }
+        board.push(placed);
+    }
+    if !NUMBER_COUNT_RANGE.contains(&board.len()) {
+        return Err(BoardTextError::Count(board.len()));
+    }
+    Ok(board)
+}
+
+// Resolves to `false` when the Clipboard API is missing (e.g. outside a secure context)
+// or the write is rejected.
+async fn copy_to_clipboard(text: &str) -> bool {
+    let navigator = window().navigator();
+    let clipboard = match js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard")) {
+        Ok(clipboard) if !clipboard.is_undefined() => {
+            clipboard.unchecked_into::<web_sys::Clipboard>()
+        }
+        _ => return false,
+    };
+    wasm_bindgen_futures::JsFuture::from(clipboard.write_text(text))
+        .await
+        .is_ok()
+}
+
+#[derive(Clone, Copy)]
+enum RankingFormat {
+    Csv,
+    Json,
+}
+
+// Best first, as the ranking page lists them.
+fn sorted_ranking(model: &Model) -> Vec<&(String, i64)> {
+    let mut entries = model
+        .ranking
+        .get(&model.ranking_tab)
+        .map(|entries| entries.iter().collect::<Vec<_>>())
+        .unwrap_or_default();
+    entries.sort_by_key(|(_, score)| *score);
+    entries
+}
+
+// `rank,name,score` rows, with fields quoted per RFC 4180 when they need it.
+fn ranking_to_csv(entries: &[&(String, i64)]) -> String {
+    let escape = |field: &str| {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_owned()
+        }
+    };
+    let mut csv = String::from("rank,name,score\r\n");
+    for (i, (name, score)) in entries.iter().enumerate() {
+        csv.push_str(&format!("{},{},{}\r\n", i + 1, escape(name), score));
+    }
+    csv
+}
+
+#[derive(Serialize)]
+struct RankingRow<'a> {
+    rank: usize,
+    name: &'a str,
+    score: i64,
+}
+
+fn ranking_to_json(entries: &[&(String, i64)]) -> String {
+    let rows = entries
+        .iter()
+        .enumerate()
+        .map(|(i, (name, score))| RankingRow {
+            rank: i + 1,
+            name,
+            score: *score,
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string_pretty(&rows).unwrap_or_default()
+}
+
+// Saves `contents` through a temporary link to an object URL. `None` if the browser
+// refused any step.
+fn download(file_name: &str, mime: &str, contents: &str) -> Option<()> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let options = web_sys::BlobPropertyBag::new();
+    options.set_type(mime);
+    let blob = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options).ok()?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).ok()?;
+    let link = document()
+        .create_element("a")
+        .ok()?
+        .unchecked_into::<web_sys::HtmlAnchorElement>();
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+    web_sys::Url::revoke_object_url(&url).ok()
+}
+
+// ------ ------
+//     Fetch
+// ------ ------
+
+const API_BASE_URL: &str = "/api";
+
+// Transient failures are retried after 500ms, then 1s, before the error is shown.
+const MAX_SUBMIT_ATTEMPTS: u32 = 3;
+const SUBMIT_RETRY_BASE_MS: u32 = 500;
+
+#[derive(Clone, Serialize)]
+struct RankingSubmission {
+    name: String,
+    // Only a claim: the server replays `moves` on the board `seed` generates and keeps the
+    // score that gives (see `verify_submission`).
+    score: i64,
+    difficulty: Difficulty,
+    // Lets the server file time-attack scores separately.
+    time_limit: Option<f64>,
+    // Likewise for move budgets.
+    move_limit: Option<u32>,
+    // Ranked apart from other targets; zero for the plain rules.
+    target: i64,
+    seed: u64,
+    number_count: usize,
+    numbers: Vec<i64>,
+    moves: Vec<Move>,
+}
+
+impl RankingSubmission {
+    // The same way `Category::of` reads it off the model the score was made on.
+    fn category(&self) -> Category {
+        Category {
+            target: self.target,
+            time_limit: self.time_limit.map(|limit| limit as u32),
+            move_limit: self.move_limit.is_some(),
+        }
+    }
+}
+
+// Where the ranking fetch for the current tab stands. The cached entries stay visible
+// throughout; this only decides what's shown around them.
+#[derive(Default)]
+enum RankingStatus {
+    Loading,
+    #[default]
+    Loaded,
+    Error(String),
+}
+
+async fn fetch_ranking(
+    rule: ScoringRule,
+    category: Category,
+    difficulty: Difficulty,
+    lang: Lang,
+) -> Msg {
+    let result = async {
+        fetch(format!(
+            "{}/{}",
+            API_BASE_URL,
+            rule.ranking_path(difficulty, category)
+        ))
+        .await?
+        .check_status()?
+        .json()
+        .await
+    };
+    match result.await {
+        Ok(ranking) => Msg::RankingFetched {
+            rule,
+            category,
+            difficulty,
+            ranking,
+        },
+        Err(err) => {
+            Msg::RankingFailed(lang.format(Text::FetchRankingFailed, &[&format!("{:?}", err)]))
+        }
+    }
+}
+
+async fn submit_ranking(rule: ScoringRule, lang: Lang, submission: RankingSubmission) -> Msg {
+    let result = async {
+        Request::new(format!(
+            "{}/{}",
+            API_BASE_URL,
+            rule.ranking_path(submission.difficulty, submission.category())
+        ))
+        .method(Method::Post)
+        .json(&submission)?
+        .fetch()
+        .await?
+        .check_status()
+    };
+    match result.await {
+        Ok(_) => Msg::RankingSubmitted,
+        Err(err) => {
+            Msg::SubmitFailed(lang.format(Text::SubmitRankingFailed, &[&format!("{:?}", err)]))
+        }
+    }
+}
+
+// ------ ------
+//     View
+// ------ ------
+
+// `view` describes what to display.
+
+const HEADER_HEIGHT: u64 = 10;
+const BOARD_HEIGHT: u64 = 40;
+const BOARD_WIDTH: u64 = 80;
+// The board may take at most this share of the window height, leaving room for the controls.
+const MAX_BOARD_HEIGHT_SHARE: f64 = 0.6;
+// Past this the numbers drift too far apart to take in at a glance, so ultrawide windows
+// get a 1600px board centered in the frame.
+const MAX_BOARD_UNIT_PX: f64 = 16.0;
+
+// CSS pixels per board unit. Board coordinates span 100 units across, so this is `1vw`
+// unless the window is too short for a board that wide, or too wide for it to stay legible.
+fn board_unit(model: &Model) -> f64 {
+    let (width, height) = model.viewport;
+    let by_width = width as f64 / 100.0;
+    let by_height = height as f64 * MAX_BOARD_HEIGHT_SHARE / BOARD_HEIGHT as f64;
+    by_width.min(by_height).min(MAX_BOARD_UNIT_PX)
+}
+
+// A length in board units, as CSS.
+fn board_px(model: &Model, units: f64) -> String {
+    px(units * board_unit(model))
+}
+
+const MAX_ZOOM: f64 = 3.0;
+
+// Stops the zoomed board from being dragged away from the frame's edges.
+fn clamp_pan(model: &mut Model) {
+    let unit = board_unit(model);
+    let (width, height) = (100.0 * unit, BOARD_HEIGHT as f64 * unit);
+    let (x, y) = model.pan;
+    model.pan = (
+        x.clamp(width * (1.0 - model.zoom), 0.0),
+        y.clamp(height * (1.0 - model.zoom), 0.0),
+    );
+}
+
+fn view(model: &Model) -> Node<Msg> {
+    div![
+        style! {
+            St::VerticalAlign => "top",
+            St::Height => vh(100),
+            St::MinHeight => vh(100),
+            St::Width => vw(100),
+            St::MinWidth => vw(100),
+            St::Background => Palette::of(model).background(),
+            St::Color => Palette::of(model).text_color(),
+        },
+        touch_ev(Ev::TouchStart, |event| {
+            let touch = event.touches().get(0)?;
+            Some(Msg::TouchStarted(
+                f64::from(touch.client_x()),
+                f64::from(touch.client_y()),
+            ))
+        }),
+        touch_ev(Ev::TouchEnd, |event| {
+            let touch = event.changed_touches().get(0)?;
+            Some(Msg::TouchEnded(
+                f64::from(touch.client_x()),
+                f64::from(touch.client_y()),
+            ))
+        }),
+        view_header(model),
+        match model.page {
+            Page::Play => view_play(model),
+            Page::Ranking => view_ranking(model),
+            Page::Settings => view_settings(model),
+        },
+        model.toast.as_ref().map(|text| view_toast(text)),
+        IF!(model.show_tutorial => view_tutorial(model)),
+        IF!(model.saved_game.is_some() => view_resume_prompt(model)),
+        IF!(model.page == Page::Play
+            && model.is_finished
+            && !model.result_closed
+            && !is_replaying(model) => view_modal(model, view_result(model))),
+    ]
+}
+
+// A centered card over a dimmed backdrop.
+fn view_modal(model: &Model, content: Node<Msg>) -> Node<Msg> {
+    div![
+        style! {
+            St::Position => "fixed",
+            St::Top => px(0),
+            St::Left => px(0),
+            St::Width => vw(100),
+            St::Height => vh(100),
+            St::Background => "rgba(0, 0, 0, 0.6)",
+            St::Display => "flex",
+            St::AlignItems => "center",
+            St::JustifyContent => "center",
+            St::ZIndex => "3",
+        },
+        div![
+            style! {
+                St::Background => Palette::of(model).result_background(),
+                St::Color => Palette::of(model).text_color(),
+                St::Padding => "30px 40px",
+                St::BorderRadius => px(12),
+                St::MaxWidth => vw(60),
+            },
+            IF!(model.high_contrast => style! {
+                St::Outline => format!(
+                    "{}px solid {}",
+                    Palette::of(model).outline_px(),
+                    Palette::of(model).text_color()
+                ),
+            }),
+            attrs! {
+                At::Role => "dialog",
+                At::from("aria-modal") => "true",
+            },
+            content,
+        ],
+    ]
+}
+
+fn view_resume_prompt(model: &Model) -> Node<Msg> {
+    view_modal(
+        model,
+        div![
+            style! {St::FontSize => px(22)},
+            p![model.lang.t(Text::SavedGameFound)],
+            button![
+                style! {St::FontSize => px(22)},
+                model.lang.t(Text::Resume),
+                ev(Ev::Click, |_| Msg::ResumeSavedGame),
+            ],
+            button![
+                style! {
+                    St::FontSize => px(22),
+                    St::MarginLeft => px(20),
+                },
+                model.lang.t(Text::StartOver),
+                ev(Ev::Click, |_| Msg::DiscardSavedGame),
+            ],
+        ],
+    )
+}
+
+fn view_tutorial(model: &Model) -> Node<Msg> {
+    view_modal(
+        model,
+        div![
+            style! {St::FontSize => px(22)},
+            h2![model.lang.t(Text::HowToPlay)],
+            ol![[
+                Text::TutorialSelect,
+                Text::TutorialCombine,
+                Text::TutorialRemove,
+                Text::TutorialScore,
+            ]
+            .iter()
+            .map(|&text| li![model.lang.t(text)])
+            .collect::<Vec<_>>()],
+            button![
+                style! {St::FontSize => px(22)},
+                model.lang.t(Text::GotIt),
+                ev(Ev::Click, |_| Msg::DismissTutorial),
+            ],
+        ],
+    )
+}
+
+fn view_toast(text: &str) -> Node<Msg> {
+    div![
+        style! {
+            St::Position => "fixed",
+            St::Bottom => vh(5),
+            St::Left => "50%",
+            St::Transform => "translateX(-50%)",
+            St::Padding => "10px 20px",
+            St::BorderRadius => px(8),
+            St::Background => "rgba(0, 0, 0, 0.8)",
+            St::Color => "white",
+            St::FontSize => px(24),
+            St::ZIndex => "2",
+        },
+        text,
+    ]
+}
+
+// Below this window width the header collapses into a menu.
+const COMPACT_HEADER_WIDTH: u64 = 900;
+
+fn view_header(model: &Model) -> Node<Msg> {
+    let is_compact = model.viewport.0 < COMPACT_HEADER_WIDTH;
+    header![
+        style! {
+            St::Background => Palette::of(model).header_background(),
+            St::Color => "white",
+            St::Height => vh(HEADER_HEIGHT),
+            St::Width => vw(100),
+            St::Padding => if is_compact {"10px 15px"} else {"20px 50px"},
+            St::BoxSizing => "border-box",
+            St::Position => "fixed",
+            St::Top => px(0),
+            St::Left => px(0),
+            St::Display => "flex",
+            St::AlignItems => "center",
+            St::FontSize => px(if is_compact {20} else {30}),
+            St::UserSelect => "none",
+            St::ZIndex => "1",
+        },
+        h1!("Algo"),
+        if is_compact {
+            view_header_menu(model)
+        } else {
+            div![
+                style! {
+                    St::Display => "flex",
+                    St::AlignItems => "center",
+                    St::FlexGrow => "1",
+                },
+                view_header_nav(model),
+                view_header_controls(model),
+            ]
+        },
+    ]
+}
+
+// The hamburger toggle and, when open, a dropdown with everything the wide header shows.
+fn view_header_menu(model: &Model) -> Node<Msg> {
+    div![
+        style! {
+            St::MarginLeft => "auto",
+            St::Position => "relative",
+        },
+        button![
+            style! {St::FontSize => px(24)},
+            attrs! {
+                At::AriaLabel => model.lang.t(Text::Menu),
+                At::AriaExpanded => model.menu_open.to_string(),
+            },
+            "☰",
+            ev(Ev::Click, |_| Msg::ToggleMenu),
+        ],
+        IF!(model.menu_open => div![
+            style! {
+                St::Position => "absolute",
+                St::Top => "100%",
+                St::Right => px(0),
+                St::Display => "flex",
+                St::FlexDirection => "column",
+                St::AlignItems => "flex-start",
+                St::Gap => px(10),
+                St::Padding => px(15),
+                St::Background => Palette::of(model).header_background(),
+                St::BoxShadow => "0 4px 12px rgba(0, 0, 0, 0.3)",
+                St::WhiteSpace => "nowrap",
+            },
+            view_header_nav(model),
+            view_header_controls(model),
+        ]),
+    ]
+}
+
+fn view_header_nav(model: &Model) -> Node<Msg> {
+    nav![ul![
+        style! {
+            St::Display => "flex",
+            St::Margin => px(0),
+            St::Padding => px(0),
+        },
+        header_li(model, Page::Play),
+        header_li(model, Page::Ranking),
+        header_li(model, Page::Settings),
+    ]]
+}
+
+fn view_header_controls(model: &Model) -> Vec<Node<Msg>> {
+    nodes![
+        div![
+            style! {St::MarginLeft => "auto"},
+            Difficulty::ALL
+                .iter()
+                .map(|&difficulty| button![
+                    style! {
+                        St::FontSize => px(20),
+                        St::MarginRight => px(5),
+                        St::FontWeight => if difficulty == model.difficulty {"bold"} else {"normal"},
+                    },
+                    model.lang.t(difficulty.label()),
+                    ev(Ev::Click, move |_| Msg::SelectDifficulty(difficulty)),
+                ])
+                .collect::<Vec<_>>(),
+        ],
+        button![
+            style! {
+                St::MarginLeft => px(20),
+                St::FontSize => px(20),
+            },
+            model.lang.format(
+                Text::ScoringRule,
+                &[&model.lang.t(model.scoring_rule.label())]
+            ),
+            ev(Ev::Click, |_| Msg::ToggleScoringRule),
+        ],
+        button![
+            style! {
+                St::MarginLeft => px(20),
+                St::FontSize => px(20),
+            },
+            model.lang.t(match model.theme {
+                Theme::Light => Text::DarkTheme,
+                Theme::Dark => Text::LightTheme,
+            }),
+            ev(Ev::Click, |_| Msg::ToggleTheme),
+        ],
+        button![
+            style! {
+                St::MarginLeft => px(20),
+                St::FontSize => px(20),
+            },
+            model.lang.t(if model.players.is_some() {Text::SinglePlayer} else {Text::Hotseat}),
+            ev(Ev::Click, |_| Msg::ToggleHotseat),
+        ],
+        button![
+            style! {
+                St::MarginLeft => px(20),
+                St::FontSize => px(20),
+                St::FontWeight => if model.time_limit.is_some() {"bold"} else {"normal"},
+            },
+            attrs! {At::AriaPressed => model.time_limit.is_some().to_string()},
+            model.lang.t(Text::TimeAttack),
+            ev(Ev::Click, |_| Msg::ToggleTimeAttack),
+        ],
+        button![
+            style! {
+                St::MarginLeft => px(20),
+                St::FontSize => px(20),
+                St::FontWeight => if model.move_limit.is_some() {"bold"} else {"normal"},
+            },
+            attrs! {At::AriaPressed => model.move_limit.is_some().to_string()},
+            model.lang.t(Text::MoveLimit),
+            ev(Ev::Click, |_| Msg::ToggleMoveLimit),
+        ],
+        button![
+            style! {
+                St::MarginLeft => px(20),
+                St::FontSize => px(20),
+            },
+            "?",
+            ev(Ev::Click, |_| Msg::ShowTutorial),
+        ],
+        button![
+            style! {
+                St::MarginLeft => px(20),
+                St::FontSize => px(20),
+            },
+            model.lang.t(if model.muted { Text::Unmute } else { Text::Mute }),
+            ev(Ev::Click, |_| Msg::ToggleMute),
+        ],
+        button![
+            style! {
+                St::MarginLeft => px(20),
+                St::FontSize => px(20),
+            },
+            model.lang.t(Text::OtherLanguage),
+            ev(Ev::Click, |_| Msg::ToggleLang),
+        ],
+        current_player_name(model).map(|name| span![
+            style! {
+                St::MarginLeft => px(20),
+                St::FontSize => px(20),
+            },
+            model.lang.format(Text::Turn, &[&name]),
+        ]),
+    ]
+}
+
+// A plain link: Seed intercepts the click and reports it as `Msg::UrlChanged`.
+fn header_li(model: &Model, page: Page) -> Node<Msg> {
+    let is_current = model.page == page;
+    li![
+        style! {
+            St::ListStyle => "none",
+            St::MarginLeft => vw(5),
+        },
+        // Focus rings come from the `nav a:focus-visible` rule in index.html.
+        a![
+            style! {
+                St::Color => "inherit",
+                St::TextDecoration => "none",
+                St::BorderRadius => px(4),
+                St::BorderBottom => if is_current {"3px solid currentColor"} else {"3px solid transparent"},
+                St::FontWeight => if is_current {"bold"} else {"normal"},
+            },
+            attrs! {At::Href => page.url(model.seed)},
+            IF!(is_current => attrs! {At::AriaCurrent => "page"}),
+            page.to_string(),
+            // Links only follow Enter natively; Space works too, as on the other header buttons.
+            keyboard_ev(Ev::KeyDown, move |event| {
+                (event.key() == " ").then(|| {
+                    event.prevent_default();
+                    Msg::Navigate(page)
+                })
+            }),
+        ],
+    ]
+}
+
+fn view_play(model: &Model) -> Node<Msg> {
+    if let Some(board) = &model.rational {
+        return view_rational_play(model, board);
+    }
+    // Three stacked regions that never overlap: the board, the scrollable game info, and
+    // the control bar pinned to the bottom.
+    div![
+        style! {
+            St::Position => "absolute",
+            St::Width => vw(100),
+            St::Height => vh(100 - HEADER_HEIGHT),
+            St::Top => vh(HEADER_HEIGHT),
+            St::Display => "flex",
+            St::FlexDirection => "column",
+        },
+        // The zoom/pan viewport onto the board.
+        div![
+            el_ref(&model.board_frame),
+            style! {
+                St::Position => "relative",
+                St::FlexShrink => "0",
+                St::Width => board_px(model, 100.0),
+                St::Height => board_px(model, BOARD_HEIGHT as f64),
+                St::Margin => "0 auto",
+                St::Overflow => "hidden",
+            },
+            // Numbers stop their clicks, so only the background gets here.
+            ev(Ev::Click, |_| Msg::ClearSelection),
+            // Trackpad pinches arrive as wheel events too, with `ctrlKey` set.
+            wheel_ev(Ev::Wheel, {
+                let frame = model.board_frame.clone();
+                move |event| {
+                    let rect = frame.get()?.get_bounding_client_rect();
+                    event.prevent_default();
+                    Some(Msg::Zoom {
+                        factor: (-event.delta_y() * 0.002).exp(),
+                        at: (
+                            f64::from(event.client_x()) - rect.left(),
+                            f64::from(event.client_y()) - rect.top(),
+                        ),
+                    })
+                }
+            }),
+            mouse_ev(Ev::MouseMove, |event| {
+                // Only while the primary button is held.
+                (event.buttons() == 1)
+                    .then(|| Msg::Pan(f64::from(event.movement_x()), f64::from(event.movement_y())))
+            }),
+            // Numbers are positioned against this 100-unit-wide board. The transform moves
+            // their boxes too, so the browser's own hit testing keeps clicks on target.
+            div![
+                style! {
+                    St::Position => "absolute",
+                    St::Top => px(0),
+                    St::Left => px(0),
+                    St::Width => "100%",
+                    St::Height => "100%",
+                    St::TransformOrigin => "0 0",
+                    St::Transform => format!(
+                        "translate({}px, {}px) scale({})",
+                        model.pan.0, model.pan.1, model.zoom
+                    ),
+                },
+                div![
+                    Palette::of(model).board_surface(),
+                    style! {
+                        St::Width => board_px(model, BOARD_WIDTH as f64),
+                        St::Height => board_px(model, BOARD_HEIGHT as f64),
+                        St::Margin => "0 auto",
+                    }
+                ],
+                // Before the live numbers, so those stay on top.
+                IF!(model.show_used && model.layout == Layout::Scattered && !model.paused => model
+                    .game
+                    .is_used
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &used)| used)
+                    .map(|(id, _)| view_used_num(model, id))
+                    .collect::<Vec<_>>()),
+                visible_ids(model)
+                    .filter(|_| !model.paused)
+                    .filter(|&id| model
+                        .animating
+                        .is_none_or(|animation| animation.mv.source != id))
+                    .map(|id| view_num(model, id))
+                    .collect::<Vec<_>>(),
+                view_animation(model),
+                view_pending_line(model),
+                view_last_move_arrow(model),
+                view_undo_ghost(model),
+            ],
+            IF!(model.paused => view_pause_overlay(model)),
+        ],
+        model
+            .combine_menu
+            .as_ref()
+            .map(|menu| view_combine_menu(model, menu)),
+        IF!(model.show_history => view_history(model)),
+        model.ai.as_ref().map(|ai| view_ai_board(model, ai)),
+        div![
+            style! {
+                // Last in the column, after the info below.
+                St::Order => "1",
+                St::FlexShrink => "0",
+                St::MaxHeight => "40%",
+                St::OverflowY => "auto",
+                St::Padding => "1vw 0 1vw 5vw",
+                St::BorderTop => "1px solid rgba(128, 128, 128, 0.4)",
+            },
+            [Operator::Add, Operator::Sub, Operator::Mul]
+                .iter()
+                .map(|&operator| {
+                    view_operator_button(Palette::of(model), operator, model.operator == operator)
+                })
+                .collect::<Vec<_>>(),
+            button![
+                style! {
+                    St::FontSize => px(20),
+                    St::MarginLeft => vw(1),
+                },
+                model.lang.format(
+                    Text::QuickMode,
+                    &[&model.lang.t(if model.quick_mode {
+                        Text::On
+                    } else {
+                        Text::Off
+                    })]
+                ),
+                ev(Ev::Click, |_| Msg::ToggleQuickMode),
+            ],
+            model
+                .game
+                .pending
+                .map(|pair| view_combine_choice(model, pair)),
+            br![],
+            span![
+                view_step_button(
+                    Palette::of(model),
+                    model.lang.t(Text::Undo),
+                    Msg::ClickedRollBack,
+                    can_undo(model)
+                ),
+                // On the wrapper, since disabled buttons don't get mouse events everywhere.
+                ev(Ev::MouseEnter, |_| Msg::UndoPreview(true)),
+                ev(Ev::MouseLeave, |_| Msg::UndoPreview(false)),
+            ],
+            view_step_button(
+                Palette::of(model),
+                model.lang.t(Text::Redo),
+                Msg::ClickedRedo,
+                !model.redo_stack.is_empty()
+            ),
+            view_step_button(
+                Palette::of(model),
+                model.lang.t(Text::UndoAll),
+                Msg::ClickedUndoAll,
+                can_undo_all(model)
+            ),
+            model.undo_budget.map(|budget| span![
+                style! {
+                    St::FontSize => px(20),
+                    St::MarginLeft => vw(1),
+                },
+                model.lang.format(Text::UndosLeft, &[&budget]),
+            ]),
+            button! {
+                model.lang.t(Text::Keypad),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                attrs! {At::AriaPressed => model.show_keypad.to_string()},
+                ev(Ev::Click, |_| Msg::ToggleKeypad),
+            },
+            button! {
+                model.lang.t(Text::NumberList),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                attrs! {At::AriaPressed => model.show_number_list.to_string()},
+                ev(Ev::Click, |_| Msg::ToggleNumberList),
+            },
+            IF!(!model.is_finished => button! {
+                model.lang.t(if model.paused {Text::Unpause} else {Text::Pause}),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                attrs! {At::AriaPressed => model.paused.to_string()},
+                ev(Ev::Click, |_| Msg::TogglePause),
+            }),
+            button! {
+                model.lang.t(Text::Reset),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                ev(Ev::Click, |_| Msg::ClickedReset),
+            },
+            button! {
+                model.lang.t(Text::NewGame),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                ev(Ev::Click, |_| Msg::ClickedNewGame),
+            },
+            button! {
+                model.lang.t(Text::Hint),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                attrs! {At::Disabled => (!subtraction_only(model)).as_at_value()},
+                ev(Ev::Click, |_| Msg::ClickedHint),
+            },
+            button! {
+                model.lang.t(match model.layout {
+                    Layout::Scattered => Text::SortLayout,
+                    Layout::Sorted => Text::ScatterLayout,
+                }),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                ev(Ev::Click, |_| Msg::ToggleLayout),
+            },
+            // Ghosts sit at their generated spots, which only the scattered layout uses.
+            IF!(model.layout == Layout::Scattered => button! {
+                model.lang.format(
+                    Text::ShowUsed,
+                    &[&model.lang.t(if model.show_used {Text::On} else {Text::Off})]
+                ),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                attrs! {At::AriaPressed => model.show_used.to_string()},
+                ev(Ev::Click, |_| Msg::ToggleShowUsed),
+            }),
+            button! {
+                model.lang.format(
+                    Text::CardMode,
+                    &[&model.lang.t(if model.card_mode {Text::On} else {Text::Off})]
+                ),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                attrs! {At::AriaPressed => model.card_mode.to_string()},
+                ev(Ev::Click, |_| Msg::ToggleCards),
+            },
+            button! {
+                model.lang.t(Text::History),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                attrs! {At::AriaExpanded => model.show_history.to_string()},
+                ev(Ev::Click, |_| Msg::ToggleHistory),
+            },
+            view_rational_toggle(model),
+            button! {
+                model.lang.format(
+                    Text::AiOpponent,
+                    &[&model.lang.t(if model.ai.is_some() {Text::On} else {Text::Off})]
+                ),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                attrs! {At::AriaPressed => model.ai.is_some().to_string()},
+                ev(Ev::Click, |_| Msg::ToggleAiOpponent),
+            },
+            button! {
+                model.lang.format(
+                    Text::DailyMode,
+                    &[&model.lang.t(if model.daily.is_some() {Text::On} else {Text::Off})]
+                ),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                attrs! {At::AriaPressed => model.daily.is_some().to_string()},
+                ev(Ev::Click, |_| Msg::ToggleDaily),
+            },
+            button! {
+                model.lang.t(Text::ShareChallenge),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                ev(Ev::Click, |_| Msg::ShareChallenge),
+            },
+            IF!(model.zoom > 1.0 => button! {
+                model.lang.t(Text::ResetView),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                ev(Ev::Click, |_| Msg::ResetView),
+            }),
+        ],
+        view_hud(model),
+        // Just above the control bar, so it can't cover the board or the controls either.
+        IF!(model.show_keypad && !model.paused => view_keypad(model)),
+        IF!(model.show_number_list && !model.paused => view_number_list(model)),
+        div![
+            IF!(model.showing_solution => p![
+                style! {
+                    St::FontSize => px(30),
+                    St::FontWeight => "bold",
+                    St::Color => "limegreen",
+                },
+                attrs! {At::Role => "status"},
+                model.lang.t(Text::PlayingSolution),
+            ]),
+            model
+                .challenge
+                .as_ref()
+                .map(|challenge| view_challenge_banner(model, challenge)),
+            model.daily.as_ref().map(|daily| p![
+                style! {
+                    St::FontSize => px(30),
+                    St::FontWeight => "bold",
+                },
+                model.lang.format(Text::DailyPuzzle, &[&daily.label()]),
+                daily.best.map(|best| span![
+                    style! {
+                        St::MarginLeft => vw(2),
+                        St::FontWeight => "normal",
+                    },
+                    model.lang.format(Text::DailyBest, &[&best]),
+                ]),
+            ]),
+            model.best_score.map(|best| p![
+                style! {St::FontSize => px(30)},
+                model.lang.format(Text::PersonalBest, &[&best])
+            ]),
+            style! {
+                St::Position => "relative",
+                St::Flex => "1",
+                St::MinHeight => px(0),
+                St::OverflowY => "auto",
+                St::Padding => "0 0 2vw 5vw",
+            },
+            view_board_stats(model),
+            view_number_count(model),
+            view_seed(model),
+            view_board_text(model),
+            view_key_legend(model),
+            IF!(model.is_finished && model.result_closed => button![
+                style! {St::FontSize => px(30)},
+                model.lang.t(Text::ShowResult),
+                ev(Ev::Click, |_| Msg::OpenResult),
+            ]),
+        ],
+    ]
+}
+
+// Consumed numbers in the order they were used, e.g. "7 → #3 (5 − 7 = -2)".
+fn view_history(model: &Model) -> Node<Msg> {
+    div![
+        style! {
+            St::Position => "absolute",
+            St::Top => px(0),
+            St::Right => vw(2),
+            St::Width => vw(18),
+            St::MaxHeight => vh(60),
+            St::OverflowY => "auto",
+            St::FontSize => px(18),
+            St::Background => Palette::of(model).result_background(),
+            St::Padding => "10px",
+            St::BorderRadius => px(8),
+        },
+        h3![
+            model.lang.t(Text::History),
+            button![
+                style! {
+                    St::MarginLeft => px(10),
+                    St::FontSize => px(14),
+                },
+                attrs! {At::AriaPressed => model.analyze.to_string()},
+                model.lang.t(Text::Analyze),
+                ev(Ev::Click, |_| Msg::ToggleAnalyze),
+            ],
+        ],
+        if model.history.is_empty() {
+            p![model.lang.t(Text::NoMovesYet)]
+        } else {
+            let durations = move_durations(&model.history);
+            let slowest = durations
+                .iter()
+                .enumerate()
+                .filter_map(|(i, duration)| Some((i, (*duration)?)))
+                .max_by_key(|&(_, duration)| duration)
+                .map(|(i, _)| i);
+            ol![model
+                .history
+                .iter()
+                .zip(durations)
+                .enumerate()
+                .map(|(i, (&mv, duration))| {
+                    let quality = model
+                        .grades
+                        .get(i)
+                        .filter(|_| model.analyze)
+                        .map(|&(_, quality)| quality);
+                    let detail = format!(
+                        "{} {} {} = {}",
+                        mv.previous,
+                        mv.operator,
+                        mv.amount,
+                        mv.result().unwrap_or_default()
+                    );
+                    li![
+                        quality.map(|quality| style! {
+                            St::BorderLeft => format!("4px solid {}", quality.color()),
+                            St::PaddingLeft => px(4),
+                        }),
+                        quality.map(|quality| attrs! {At::Title => model.lang.t(quality.label())}),
+                        model
+                            .lang
+                            .format(Text::HistoryEntry, &[&mv.amount, &(mv.target + 1), &detail]),
+                        duration.map(|duration| span![
+                            style! {
+                                St::MarginLeft => px(6),
+                                St::Opacity => "0.7",
+                            },
+                            model.lang.format(
+                                Text::MoveDuration,
+                                &[&format!("{:.1}", f64::from(duration) / 1000.0)]
+                            ),
+                        ]),
+                        IF!(slowest == Some(i) => span![
+                            style! {
+                                St::MarginLeft => px(6),
+                                St::FontWeight => "bold",
+                                St::Color => "orange",
+                            },
+                            model.lang.t(Text::SlowestMove),
+                        ]),
+                    ]
+                })
+                .collect::<Vec<_>>()]
+        },
+    ]
+}
+
+// How long each move took, from the one before it (or the first move). `None` for moves
+// made without the clock running, such as replays of a saved game.
+fn move_durations(history: &[Move]) -> Vec<Option<u32>> {
+    let mut previous = Some(0);
+    history
+        .iter()
+        .map(|mv| {
+            let duration = mv
+                .elapsed_ms
+                .zip(previous)
+                .map(|(at, previous)| at.saturating_sub(previous));
+            previous = mv.elapsed_ms;
+            duration
+        })
+        .collect()
+}
+
+fn view_progress(model: &Model) -> Node<Msg> {
+    let percent = model.game.progress() * 100.0;
+    div![
+        style! {
+            St::Width => px(300),
+            St::MaxWidth => "80%",
+            St::Height => px(12),
+            St::BorderRadius => px(6),
+            St::Background => "rgba(128, 128, 128, 0.3)",
+            St::Overflow => "hidden",
+        },
+        attrs! {
+            At::Role => "progressbar",
+            At::AriaValueMin => 0,
+            At::AriaValueMax => 100,
+            At::AriaValueNow => percent.round(),
+            At::AriaLabel => model.lang.t(Text::Progress),
+        },
+        div![style! {
+            St::Width => format!("{}%", percent),
+            St::Height => "100%",
+            St::Background => Palette::of(model).selected_color(),
+            St::Transition => "width 0.2s ease-out",
+        }],
+    ]
+}
+
+fn view_board_stats(model: &Model) -> Node<Msg> {
+    let (sum, min, max) = board_stats(model);
+    // `a ± b` has the same parity as `a + b`, so without `×` the sum's parity is the final one.
+    let parity = model
+        .lang
+        .t(if sum % 2 == 0 { Text::Even } else { Text::Odd });
+    p![
+        style! {St::FontSize => px(20)},
+        model
+            .lang
+            .format(Text::BoardStats, &[&sum, &parity, &min, &max]),
+    ]
+}
+
+// -/+ steppers for the number of numbers on the board.
+fn view_number_count(model: &Model) -> Node<Msg> {
+    let count = model.game.numbers.len();
+    let stepper = |label: &'static str, count: usize, is_enabled: bool| {
+        button![
+            style! {
+                St::FontSize => px(20),
+                St::MarginLeft => vw(1),
+            },
+            attrs! {At::Disabled => (!is_enabled).as_at_value()},
+            label,
+            ev(Ev::Click, move |_| Msg::SetNumberCount(count)),
+        ]
+    };
+    div![
+        style! {St::FontSize => px(20)},
+        model.lang.format(Text::NumberCount, &[&count]),
+        stepper(
+            "−",
+            count.saturating_sub(1),
+            count > *NUMBER_COUNT_RANGE.start()
+        ),
+        stepper("+", count + 1, count < *NUMBER_COUNT_RANGE.end()),
+    ]
+}
+
+fn view_seed(model: &Model) -> Node<Msg> {
+    div![
+        style! {St::FontSize => px(20)},
+        match &model.seed_phrase {
+            Some(phrase) => model.lang.format(Text::PhraseSeed, &[phrase, &model.seed]),
+            None => model.lang.format(Text::Seed, &[&model.seed]),
+        },
+        input! {
+            style!{St::MarginLeft => vw(1)},
+            attrs!{
+                At::Type => "text",
+                At::Placeholder => model.lang.t(Text::SeedPlaceholder),
+            },
+            input_ev(Ev::Change, Msg::LoadSeed),
+        },
+        input! {
+            style!{St::MarginLeft => vw(1)},
+            attrs!{
+                At::Type => "text",
+                At::Placeholder => model.lang.t(Text::PhrasePlaceholder),
+            },
+            input_ev(Ev::Change, Msg::SetSeedFromText),
+        },
+        model.seed_error.as_ref().map(|err| span![
+            style! {
+                St::Color => "red",
+                St::MarginLeft => vw(1),
+            },
+            err,
+        ]),
+    ]
+}
+
+fn view_board_text(model: &Model) -> Node<Msg> {
+    div![
+        style! {St::FontSize => px(20)},
+        button![
+            model.lang.t(Text::ExportBoard),
+            ev(Ev::Click, |_| Msg::ExportBoard),
+        ],
+        textarea![
+            style! {
+                St::Display => "block",
+                St::Width => px(240),
+                St::Height => px(80),
+                St::MarginTop => px(5),
+                St::FontFamily => "monospace",
+            },
+            attrs! {At::Placeholder => model.lang.t(Text::ImportBoardPlaceholder)},
+            input_ev(Ev::Change, Msg::ImportBoard),
+        ],
+        model
+            .board_error
+            .as_ref()
+            .map(|err| span![style! {St::Color => "red"}, err]),
+    ]
+}
+
+fn view_key_legend(model: &Model) -> Node<Msg> {
+    div![
+        style! {
+            St::FontSize => px(16),
+            St::Color => "gray",
+        },
+        model.lang.t(Text::KeyLegend),
+        KEY_LABELS
+            .iter()
+            .zip(visible_ids(model))
+            .map(|(label, id)| span![
+                style! {St::MarginRight => px(10)},
+                format!("[{}] {}", label, model.game.numbers[id]),
+            ])
+            .collect::<Vec<_>>(),
+    ]
+}
+
+// Freezes at `end_time` once the game is finished, and while paused.
+fn elapsed_ms(model: &Model) -> f64 {
+    model.start_time.map_or(0.0, |start| {
+        let now = if model.paused {
+            model.paused_at
+        } else {
+            model.end_time.unwrap_or_else(js_sys::Date::now)
+        };
+        now - start - model.paused_ms
+    })
+}
+
+fn elapsed_seconds(model: &Model) -> f64 {
+    elapsed_ms(model) / 1000.0
+}
+
+// "A−B" / "B−A": the left operand survives.
+fn view_combine_choice(model: &Model, (first, second): (usize, usize)) -> Node<Msg> {
+    let (a, b) = (model.game.numbers[first], model.game.numbers[second]);
+    let choice = |keep_first: bool, lhs: i64, rhs: i64| {
+        button![
+            style! {
+                St::FontSize => px(30),
+                St::MarginLeft => vw(1),
+            },
+            format!("{} {} {}", lhs, model.operator, rhs),
+            ev(Ev::Click, move |_| Msg::ConfirmCombine(keep_first)),
+        ]
+    };
+    span![
+        style! {
+            St::FontSize => px(20),
+            St::MarginLeft => vw(2),
+        },
+        model.lang.t(Text::ChooseDirection),
+        choice(true, a, b),
+        choice(false, b, a),
+    ]
+}
+
+// The numbers still on the board, ascending, ties in board order.
+fn sorted_live_ids(model: &Model) -> Vec<usize> {
+    let mut ids = visible_ids(model).collect::<Vec<_>>();
+    ids.sort_by_key(|&id| (model.game.numbers[id], id));
+    ids
+}
+
+// Tells equal values apart: `Some(k)` for the k-th of several copies in `ids`.
+fn duplicate_rank(model: &Model, ids: &[usize], id: usize) -> Option<usize> {
+    let value = model.game.numbers[id];
+    let same = ids.iter().filter(|&&i| model.game.numbers[i] == value);
+    (same.clone().count() > 1).then(|| same.filter(|&&i| i <= id).count())
+}
+
+// The same numbers as a narrow column at the side, for reading a dense board at a glance.
+fn view_number_list(model: &Model) -> Node<Msg> {
+    let ids = sorted_live_ids(model);
+    ol![
+        style! {
+            St::Position => "absolute",
+            St::Top => px(0),
+            St::Right => px(0),
+            St::MaxHeight => "100%",
+            St::OverflowY => "auto",
+            St::Margin => px(0),
+            St::Padding => px(8),
+            St::ListStyle => "none",
+            St::Background => Palette::of(model).result_background(),
+            St::ZIndex => "3",
+        },
+        attrs! {At::AriaLabel => model.lang.t(Text::NumberList)},
+        ids.iter()
+            .map(|&id| {
+                let is_selected = model.game.selected == Some(id);
+                li![button![
+                    style! {
+                        St::Display => "block",
+                        St::Width => "100%",
+                        St::MinWidth => px(72),
+                        St::MinHeight => px(36),
+                        St::MarginBottom => px(4),
+                        St::FontSize => px(22),
+                        St::TextAlign => "right",
+                        St::FontWeight => if is_selected {"bold"} else {"normal"},
+                        St::Outline => if is_selected {
+                            format!("3px solid {}", Palette::of(model).selected_color())
+                        } else {
+                            "none".to_owned()
+                        },
+                    },
+                    attrs! {At::AriaPressed => is_selected.to_string()},
+                    format_number(model.game.numbers[id], &model.number_format),
+                    duplicate_rank(model, &ids, id)
+                        .map(|n| sub![style! {St::FontSize => px(12)}, n.to_string()]),
+                    ev(Ev::Click, move |_| Msg::Clicked(id)),
+                    ev(Ev::MouseEnter, move |_| Msg::Hovered(Some(id))),
+                    ev(Ev::MouseLeave, |_| Msg::Hovered(None)),
+                ]]
+            })
+            .collect::<Vec<_>>(),
+    ]
+}
+
+// The numbers left as big buttons, smallest first, for screens where the board's numbers
+// are hard to hit. Each button is one board number and sends the usual click for it;
+// repeated values are told apart by a 1-based hint, and hovering one previews the combine
+// just like hovering the number itself.
+fn view_keypad(model: &Model) -> Node<Msg> {
+    let ids = sorted_live_ids(model);
+    div![
+        style! {
+            St::Order => "1",
+            St::FlexShrink => "0",
+            St::Display => "flex",
+            St::FlexWrap => "wrap",
+            St::JustifyContent => "center",
+            St::Gap => px(8),
+            St::Padding => px(8),
+            St::Background => Palette::of(model).result_background(),
+        },
+        attrs! {At::AriaLabel => model.lang.t(Text::Keypad)},
+        ids.iter()
+            .map(|&id| {
+                let is_selected = model.game.selected == Some(id);
+                button![
+                    style! {
+                        St::MinWidth => px(64),
+                        St::MinHeight => px(56),
+                        St::FontSize => px(28),
+                        St::FontWeight => if is_selected {"bold"} else {"normal"},
+                        St::Outline => if is_selected {
+                            format!("3px solid {}", Palette::of(model).selected_color())
+                        } else {
+                            "none".to_owned()
+                        },
+                    },
+                    attrs! {At::AriaPressed => is_selected.to_string()},
+                    format_number(model.game.numbers[id], &model.number_format),
+                    duplicate_rank(model, &ids, id)
+                        .map(|n| sub![style! {St::FontSize => px(14)}, n.to_string()]),
+                    ev(Ev::Click, move |_| Msg::Clicked(id)),
+                    ev(Ev::MouseEnter, move |_| Msg::Hovered(Some(id))),
+                    ev(Ev::MouseLeave, |_| Msg::Hovered(None)),
+                ]
+            })
+            .collect::<Vec<_>>(),
+    ]
+}
+
+// A click anywhere else dismisses it, via the full-screen backdrop behind it.
+fn view_combine_menu(model: &Model, menu: &CombineMenu) -> Node<Msg> {
+    let (target, source) = (
+        model.game.numbers[menu.target],
+        model.game.numbers[menu.source],
+    );
+    div![
+        style! {
+            St::Position => "fixed",
+            St::Top => px(0),
+            St::Left => px(0),
+            St::Width => vw(100),
+            St::Height => vh(100),
+            St::ZIndex => "10",
+        },
+        ev(Ev::Click, |_| Msg::CloseCombineMenu),
+        mouse_ev(Ev::ContextMenu, |event| {
+            event.prevent_default();
+            Msg::CloseCombineMenu
+        }),
+        div![
+            style! {
+                St::Position => "absolute",
+                St::Left => px(menu.at.0),
+                St::Top => px(menu.at.1),
+                St::Display => "flex",
+                St::FlexDirection => "column",
+                St::Background => Palette::of(model).result_background(),
+                St::BorderRadius => px(6),
+                St::BoxShadow => "0 2px 8px rgba(0, 0, 0, 0.4)",
+                St::Padding => px(4),
+            },
+            attrs! {At::Role => "menu"},
+            [Operator::Add, Operator::Sub, Operator::Mul]
+                .iter()
+                .map(|&operator| {
+                    let result = operator.apply(target, source);
+                    button![
+                        style! {
+                            St::FontSize => px(20),
+                            St::TextAlign => "left",
+                        },
+                        attrs! {
+                            At::Role => "menuitem",
+                            At::Disabled => result.is_none().as_at_value(),
+                        },
+                        format!(
+                            "{} {} {} = {}",
+                            target,
+                            operator,
+                            source,
+                            result.map_or_else(|| "—".to_owned(), |n| n.to_string())
+                        ),
+                        ev(Ev::Click, move |event| {
+                            event.stop_propagation();
+                            Msg::ChooseCombine(operator)
+                        }),
+                    ]
+                })
+                .collect::<Vec<_>>(),
+        ],
+    ]
+}
+
+// The live indicators, in a strip between the scrolling info and the keypad/controls, so
+// they stay on screen however the board is zoomed or the info scrolled.
+fn view_hud(model: &Model) -> Node<Msg> {
+    let collapsed = model.hud_collapsed;
+    div![
+        style! {
+            St::Order => "1",
+            St::FlexShrink => "0",
+            St::Display => "flex",
+            St::FlexWrap => "wrap",
+            St::AlignItems => "center",
+            St::ColumnGap => px(24),
+            St::Padding => "0.5vw 0 0.5vw 5vw",
+            St::BorderTop => "1px solid rgba(128, 128, 128, 0.4)",
+            St::FontSize => px(24),
+        },
+        button![
+            style! {St::FontSize => px(18)},
+            attrs! {
+                At::AriaExpanded => (!collapsed).to_string(),
+                At::AriaLabel => model.lang.t(Text::Hud),
+            },
+            if collapsed { "▸" } else { "▾" },
+            ev(Ev::Click, |_| Msg::ToggleHud),
+        ],
+        IF!(!collapsed => vec![
+            span![model.lang.format(Text::Moves, &[&model.move_count])],
+            span![IF!(model.scoring_rule.uses_target() => model
+                .lang
+                .format(Text::TargetValue, &[&model.target]))],
+            view_progress(model),
+            span![
+                style! {
+                    St::Color => if model.optimal_reachable || !subtraction_only(model) {
+                        "inherit"
+                    } else {
+                        "orange"
+                    },
+                },
+                IF!(!model.is_finished => model
+                    .lang
+                    .format(Text::ReachableBest, &[&model.best_reachable])),
+                IF!(!model.is_finished && !subtraction_only(model) => model
+                    .lang
+                    .t(Text::SubtractionOnly)),
+            ],
+            span![model
+                .lang
+                .format(Text::Elapsed, &[&format!("{:.1}", elapsed_seconds(model))])],
+        ]),
+        // Kept even when collapsed, like the clock below.
+        remaining_moves(model).map(|moves| span![
+            style! {
+                St::FontSize => px(32),
+                St::FontWeight => "bold",
+                St::Color => if moves <= 1 {"red"} else {"inherit"},
+            },
+            attrs! {At::Role => "status"},
+            model.lang.format(Text::MovesLeft, &[&moves]),
+        ]),
+        // Kept even when collapsed: running out of time matters too much to hide.
+        remaining_seconds(model).map(|seconds| span![
+            style! {
+                St::FontSize => px(32),
+                St::FontWeight => "bold",
+                St::Color => if seconds <= 10.0 {"red"} else {"inherit"},
+            },
+            attrs! {At::Role => "timer"},
+            model
+                .lang
+                .format(Text::TimeLeft, &[&format!("{:.1}", seconds)])
+        ]),
+    ]
+}
+
+// Covers the board frame; the numbers themselves aren't even rendered while paused.
+fn view_pause_overlay(model: &Model) -> Node<Msg> {
+    div![
+        style! {
+            St::Position => "absolute",
+            St::Top => px(0),
+            St::Left => px(0),
+            St::Width => "100%",
+            St::Height => "100%",
+            St::Display => "flex",
+            St::AlignItems => "center",
+            St::JustifyContent => "center",
+            St::Background => Palette::of(model).result_background(),
+            St::FontSize => px(36),
+            St::Cursor => "pointer",
+            St::ZIndex => "5",
+        },
+        attrs! {
+            At::Role => "button",
+            At::TabIndex => 0,
+        },
+        model.lang.t(Text::PausedOverlay),
+        ev(Ev::Click, |event| {
+            event.stop_propagation();
+            Msg::TogglePause
+        }),
+    ]
+}
+
+fn view_challenge_banner(model: &Model, challenge: &Challenge) -> Node<Msg> {
+    p![
+        style! {
+            St::FontSize => px(30),
+            St::FontWeight => "bold",
+            St::Color => "orange",
+        },
+        attrs! {At::Role => "status"},
+        model.lang.t(Text::ChallengeBanner),
+        challenge.score.map(|score| span![
+            style! {
+                St::MarginLeft => vw(2),
+                St::FontWeight => "normal",
+            },
+            model.lang.format(Text::ChallengeScore, &[&score]),
+        ]),
+    ]
+}
+
+// Scale of the AI's board next to the player's.
+const AI_BOARD_SCALE: f64 = 0.3;
+
+// The AI's copy of the board, shrunk, in the same layout as the player's.
+fn view_ai_board(model: &Model, ai: &AiOpponent) -> Node<Msg> {
+    let unit = board_unit(model) * AI_BOARD_SCALE;
+    div![
+        style! {
+            St::Position => "absolute",
+            St::Top => px(0),
+            St::Left => vw(2),
+            St::Width => px(100.0 * unit),
+            St::Height => px(BOARD_HEIGHT as f64 * unit),
+            St::PointerEvents => "none",
+        },
+        Palette::of(model).board_surface(),
+        attrs! {At::AriaLabel => model.lang.t(Text::AiBoard)},
+        span![
+            style! {
+                St::Position => "absolute",
+                St::Bottom => "100%",
+                St::FontSize => px(16),
+            },
+            model.lang.t(Text::AiBoard),
+        ],
+        ai.game
+            .is_used
+            .iter()
+            .enumerate()
+            .filter(|(_, &used)| !used)
+            .map(|(id, _)| {
+                let (x, y) = model.points[id];
+                span![
+                    style! {
+                        St::Position => "absolute",
+                        St::Left => px(x as f64 * unit),
+                        St::Top => px(y as f64 * unit),
+                        St::FontSize => px(16),
+                        St::Color => Palette::of(model).number_color(),
+                    },
+                    ai.game.numbers[id].to_string(),
+                ]
+            })
+            .collect::<Vec<_>>(),
+    ]
+}
+
+fn view_rational_toggle(model: &Model) -> Node<Msg> {
+    button! {
+        model.lang.format(
+            Text::RationalMode,
+            &[&model.lang.t(if model.rational.is_some() {Text::On} else {Text::Off})]
+        ),
+        style!{
+            St::FontSize => px(30),
+            St::MarginLeft => vw(2),
+        },
+        attrs! {At::AriaPressed => model.rational.is_some().to_string()},
+        ev(Ev::Click, |_| Msg::ToggleRationalMode),
+    }
+}
+
+// Rational mode's stripped-down play screen: the board, the four operators and a result line.
+fn view_rational_play(model: &Model, board: &RationalBoard) -> Node<Msg> {
+    let theme = Palette::of(model);
+    div![
+        style! {
+            St::Position => "absolute",
+            St::Width => vw(100),
+            St::Height => vh(100 - HEADER_HEIGHT),
+            St::Top => vh(HEADER_HEIGHT),
+        },
+        div![
+            style! {
+                St::Position => "relative",
+                St::Width => board_px(model, 100.0),
+                St::Height => board_px(model, BOARD_HEIGHT as f64),
+                St::Margin => "0 auto",
+            },
+            ev(Ev::Click, |_| Msg::ClearSelection),
+            div![theme.board_surface(), style! {
+                St::Width => board_px(model, BOARD_WIDTH as f64),
+                St::Height => board_px(model, BOARD_HEIGHT as f64),
+                St::Margin => "0 auto",
+            }],
+            board
+                .game
+                .is_used
+                .iter()
+                .enumerate()
+                .filter(|(_, &used)| !used)
+                .map(|(id, _)| {
+                    let (x, y) = board.points[id];
+                    let value = board.game.numbers[id];
+                    let is_selected = board.game.selected == Some(id);
+                    div![
+                        attrs! {
+                            At::Role => "button",
+                            At::TabIndex => 0,
+                            At::AriaPressed => is_selected.to_string(),
+                            At::AriaLabel => model.lang.format(Text::NumberLabel, &[&value]),
+                        },
+                        style! {
+                            St::Position => "absolute",
+                            St::Left => board_px(model, x as f64),
+                            St::Top => board_px(model, y as f64),
+                            St::MinWidth => px(44),
+                            St::MinHeight => px(44),
+                            St::Display => "flex",
+                            St::AlignItems => "center",
+                            St::JustifyContent => "center",
+                            St::Cursor => "pointer",
+                            St::FontSize => px(40),
+                            St::UserSelect => "none",
+                            St::Color => if is_selected {theme.selected_color()} else {theme.number_color()},
+                        },
+                        IF!(is_selected => style! {
+                            St::Outline => format!("3px solid {}", theme.selected_color()),
+                            St::BorderRadius => px(8),
+                        }),
+                        value.to_string(),
+                        ev(Ev::Click, move |event| {
+                            event.stop_propagation();
+                            Msg::Clicked(id)
+                        }),
+                        keyboard_ev(Ev::KeyDown, move |event| {
+                            matches!(event.key().as_str(), "Enter" | " ").then(|| {
+                                event.prevent_default();
+                                Msg::Clicked(id)
+                            })
+                        }),
+                    ]
+                })
+                .collect::<Vec<_>>(),
+        ],
+        div![
+            style! {St::Padding => "0 0 5vw 5vw"},
+            [Operator::Add, Operator::Sub, Operator::Mul, Operator::Div]
+                .iter()
+                .map(|&operator| {
+                    view_operator_button(Palette::of(model), operator, model.operator == operator)
+                })
+                .collect::<Vec<_>>(),
+            button! {
+                model.lang.t(Text::Reset),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                ev(Ev::Click, |_| Msg::ClickedReset),
+            },
+            button! {
+                model.lang.t(Text::NewGame),
+                style!{
+                    St::FontSize => px(30),
+                    St::MarginLeft => vw(2),
+                },
+                ev(Ev::Click, |_| Msg::ClickedNewGame),
+            },
+            view_rational_toggle(model),
+            board.game.final_number().filter(|_| board.game.is_finished()).map(|value| p![
+                style! {St::FontSize => px(30)},
+                attrs! {At::AriaLive => "polite"},
+                model.lang.format(Text::RationalResult, &[&value, &value.abs()]),
+                board.best.map(|best| span![
+                    style! {St::MarginLeft => vw(2)},
+                    model.lang.format(Text::Optimal, &[&best.abs()]),
+                ]),
+            ]),
+        ],
+    ]
+}
+
+fn view_operator_button(palette: Palette, operator: Operator, is_selected: bool) -> Node<Msg> {
+    let (background, color) = palette.choice_colors(is_selected);
+    button! {
+        operator.to_string(),
+        style!{
+            St::FontSize => px(30),
+            St::MarginRight => vw(1),
+            St::Background => background,
+            St::Color => color,
+        },
+        ev(Ev::Click, move |_| Msg::SelectOperator(operator)),
+    }
 }
 
-// `update` describes how to handle each `Msg`.
-fn update(msg: Msg, model: &mut Model, _: &mut impl Orders<Msg>) {
-    match msg {
-        Msg::Clicked(id) => match model.selected {
-            Some(a_id) => {
-                if a_id != id {
-                    model.numbers[a_id] -= model.numbers[id];
-                    model.is_used[id] = true;
-                }
-                model.selected = None;
-
-                if model.is_used.iter().filter(|&&b| b).count() + 1 == model.is_used.len() {
-                    model.is_finished = true;
-                }
-            }
-            None => {
-                model.selected = Some(id);
-            }
+// Undo/redo: grayed out and inert when there's nothing to step to.
+fn view_step_button(palette: Palette, label: &str, msg: Msg, is_enabled: bool) -> Node<Msg> {
+    button! {
+        label,
+        style!{
+            St::FontSize => px(30),
+            St::MarginLeft => vw(2),
+            St::Color => palette.step_color(is_enabled),
         },
-        Msg::ClickedPlay => model.page = Page::Play,
-        Msg::ClickedRanking => model.page = Page::Ranking,
-        Msg::ClickedRollBack => {}
-        Msg::ClickedSendButton => {}
-        Msg::ChangedTextArea(str) => model.name = str,
+        attrs!{At::Disabled => (!is_enabled).as_at_value()},
+        IF!(is_enabled => ev(Ev::Click, move |_| msg)),
     }
 }
 
-// ------ ------
-//     View
-// ------ ------
+// The sorted grid's columns: six up to 18 numbers, widening to eight (`10 + 12 * 7` still
+// clears the edge margin) before the rows are squeezed together.
+const SORTED_COLUMNS: std::ops::RangeInclusive<u64> = 6..=8;
+const SORTED_COLUMN_STEP: u64 = 12;
+const SORTED_ROW_STEP: u64 = 10;
+const SORTED_ORIGIN: (u64, u64) = (10, 6);
+// Board units kept clear at the right and bottom edges for a number's tap box.
+const NUM_EDGE_MARGIN: u64 = 6;
 
-// `view` describes what to display.
+// Slot `rank` of a sorted grid sized for `slots` numbers, every row inside the board.
+fn sorted_slot(rank: usize, slots: usize) -> (u64, u64) {
+    let (rank, slots) = (rank as u64, slots.max(1) as u64);
+    let columns = slots
+        .div_ceil(3)
+        .clamp(*SORTED_COLUMNS.start(), *SORTED_COLUMNS.end());
+    let rows = slots.div_ceil(columns);
+    let height = BOARD_HEIGHT - NUM_EDGE_MARGIN - SORTED_ORIGIN.1;
+    let row_step = SORTED_ROW_STEP.min(height / rows.saturating_sub(1).max(1));
+    let (col, row) = (rank % columns, rank / columns);
+    (
+        SORTED_ORIGIN.0 + SORTED_COLUMN_STEP * col,
+        SORTED_ORIGIN.1 + row_step * row,
+    )
+}
 
-const HEADER_HEIGHT: u64 = 10;
-const BOARD_HEIGHT: u64 = 40;
-const BOARD_WIDTH: u64 = 80;
+// Where number `id` is drawn, in the same coordinates as `Model::points`.
+fn number_position(model: &Model, id: usize) -> (u64, u64) {
+    match model.layout {
+        Layout::Scattered => {
+            // Imported boards may put a number right at the edge; keep its whole tap box on
+            // the board.
+            let (x, y) = model.points[id];
+            (
+                x.min(100 - NUM_EDGE_MARGIN),
+                y.min(BOARD_HEIGHT - NUM_EDGE_MARGIN),
+            )
+        }
+        Layout::Sorted => {
+            let ids = sorted_live_ids(model);
+            // A number off the board (the undo preview's ghost) takes the next free slot.
+            // Sized for the whole deal, so the grid stays put as numbers are used up.
+            let rank = ids.iter().position(|&i| i == id).unwrap_or(ids.len());
+            sorted_slot(rank, model.game.numbers.len())
+        }
+    }
+}
 
-fn view(model: &Model) -> Node<Msg> {
-    div![
+// The number the undo button would bring back, ghosted in while the button is hovered.
+fn view_undo_ghost(model: &Model) -> Option<Node<Msg>> {
+    if !model.undo_preview || !can_undo(model) {
+        return None;
+    }
+    let mv = model.history.last()?;
+    let (x, y) = number_position(model, mv.source);
+    Some(div![
         style! {
-            St::VerticalAlign => "top",
-            St::Height => vh(100),
-            St::MinHeight => vh(100),
-            St::Width => vw(100),
-            St::MinWidth => vw(100),
+            St::Position => "absolute",
+            St::Left => board_px(model, x as f64),
+            St::Top => board_px(model, y as f64),
+            St::MinWidth => px(44),
+            St::MinHeight => px(44),
+            St::Display => "flex",
+            St::AlignItems => "center",
+            St::JustifyContent => "center",
+            St::Opacity => "0.5",
+            St::Outline => format!("2px dashed {}", Palette::of(model).number_color()),
+            St::BorderRadius => px(8),
+            St::PointerEvents => "none",
         },
-        view_header(),
-        match model.page {
-            Page::Play => view_play(model),
-            Page::Ranking => a!("ranking"),
-        }
-    ]
+        attrs! {At::AriaHidden => "true"},
+        span![
+            style! {
+                St::FontSize => px(40),
+                St::Color => Palette::of(model).number_color(),
+            },
+            mv.amount.to_string(),
+        ],
+    ])
 }
 
-fn view_header() -> Node<Msg> {
-    header![
+// Roughly the middle of a number's tap box, in board units from its top-left corner.
+const NUM_CENTER_OFFSET: f64 = 1.5;
+
+// Shares the numbers' coordinate space, so positions can be used as-is.
+fn view_pending_line(model: &Model) -> Option<Node<Msg>> {
+    let from = number_position(model, model.game.selected?);
+    let to = number_position(
+        model,
+        model
+            .hovered
+            .filter(|&id| Some(id) != model.game.selected)?,
+    );
+    let center = |(x, y): (u64, u64)| (x as f64 + NUM_CENTER_OFFSET, y as f64 + NUM_CENTER_OFFSET);
+    let ((x1, y1), (x2, y2)) = (center(from), center(to));
+    Some(svg![
         style! {
-            St::Background => "#4385f4",
-            St::Color => "white",
-            St::Height => vh(HEADER_HEIGHT),
-            St::Width => vw(100),
-            St::Padding => "20px 50px",
-            St::BoxSizing => "border-box",
-            St::Position => "fixed",
+            St::Position => "absolute",
+            St::Top => px(0),
+            St::Left => px(0),
+            St::Width => board_px(model, 100.0),
+            St::Height => board_px(model, BOARD_HEIGHT as f64),
+            St::PointerEvents => "none",
+        },
+        attrs! {
+            At::ViewBox => format!("0 0 100 {}", BOARD_HEIGHT),
+            At::PreserveAspectRatio => "none",
+        },
+        line_![attrs! {
+            At::X1 => x1,
+            At::Y1 => y1,
+            At::X2 => x2,
+            At::Y2 => y2,
+            At::Stroke => Palette::of(model).selected_color(),
+            At::StrokeWidth => "0.3",
+            At::StrokeDashArray => "1 0.6",
+            At::StrokeLinecap => "round",
+        }],
+    ])
+}
+
+const LAST_MOVE_FADE_MS: u32 = 1500;
+// Length and half-width of the arrowhead, in board units.
+const ARROWHEAD_SIZE: (f64, f64) = (2.0, 1.0);
+
+// Where the last move came from, fading out once the number has landed.
+fn view_last_move_arrow(model: &Model) -> Option<Node<Msg>> {
+    if model.animating.is_some() || model.paused {
+        return None;
+    }
+    let mv = model.history.last()?;
+    let center = |(x, y): (u64, u64)| (x as f64 + NUM_CENTER_OFFSET, y as f64 + NUM_CENTER_OFFSET);
+    let (x1, y1) = center(number_position(model, mv.source));
+    let (x2, y2) = center(number_position(model, mv.target));
+    let length = (x2 - x1).hypot(y2 - y1);
+    if length < ARROWHEAD_SIZE.0 {
+        return None;
+    }
+    // Stop short of the target's center so the head doesn't cover the digits.
+    let (dx, dy) = ((x2 - x1) / length, (y2 - y1) / length);
+    let (tip_x, tip_y) = (x2 - dx * NUM_CENTER_OFFSET, y2 - dy * NUM_CENTER_OFFSET);
+    let (back_x, back_y) = (tip_x - dx * ARROWHEAD_SIZE.0, tip_y - dy * ARROWHEAD_SIZE.0);
+    let (side_x, side_y) = (-dy * ARROWHEAD_SIZE.1, dx * ARROWHEAD_SIZE.1);
+    let color = Palette::of(model).selected_color();
+    Some(svg![
+        style! {
+            St::Position => "absolute",
             St::Top => px(0),
             St::Left => px(0),
+            St::Width => board_px(model, 100.0),
+            St::Height => board_px(model, BOARD_HEIGHT as f64),
+            St::PointerEvents => "none",
+            // Renaming the animation on every move restarts the fade.
+            St::Animation => format!(
+                "algo-last-move-{} {}ms ease-in forwards",
+                model.history.len() % 2,
+                LAST_MOVE_FADE_MS
+            ),
+        },
+        attrs! {
+            At::ViewBox => format!("0 0 100 {}", BOARD_HEIGHT),
+            At::PreserveAspectRatio => "none",
+            At::AriaHidden => "true",
+        },
+        line_![attrs! {
+            At::X1 => x1,
+            At::Y1 => y1,
+            At::X2 => back_x,
+            At::Y2 => back_y,
+            At::Stroke => color,
+            At::StrokeWidth => "0.4",
+            At::StrokeLinecap => "round",
+        }],
+        polygon![attrs! {
+            At::Points => format!(
+                "{},{} {},{} {},{}",
+                tip_x,
+                tip_y,
+                back_x + side_x,
+                back_y + side_y,
+                back_x - side_x,
+                back_y - side_y
+            ),
+            At::Fill => color,
+        }],
+    ])
+}
+
+// The consumed number part-way to its target, shrinking and fading as it goes.
+fn view_animation(model: &Model) -> Option<Node<Msg>> {
+    let Animation { mv, started } = model.animating?;
+    let t = ((js_sys::Date::now() - started) / f64::from(ANIMATION_DURATION_MS)).min(1.0);
+    // Ease-in, so it visibly leaves before speeding into the target.
+    let eased = t * t;
+    let (from, to) = (
+        number_position(model, mv.source),
+        number_position(model, mv.target),
+    );
+    let lerp = |a: u64, b: u64| a as f64 + (b as f64 - a as f64) * eased;
+    Some(div![
+        style! {
+            St::Position => "absolute",
+            St::Left => board_px(model, lerp(from.0, to.0)),
+            St::Top => board_px(model, lerp(from.1, to.1)),
+            St::MinWidth => px(44),
+            St::MinHeight => px(44),
             St::Display => "flex",
             St::AlignItems => "center",
-            St::FontSize => px(30),
-            St::UserSelect => "none",
-            St::ZIndex => "1",
+            St::JustifyContent => "center",
+            St::PointerEvents => "none",
+            St::ZIndex => "2",
+            St::Opacity => 1.0 - 0.6 * eased,
+            St::Transform => format!("scale({})", 1.0 - 0.4 * eased),
         },
-        h1!("Algo"),
-        ul![
-            style! {St::Display => "flex"},
-            header_li(Page::Play, Msg::ClickedPlay),
-            header_li(Page::Ranking, Msg::ClickedRanking),
-        ]
-    ]
+        span![
+            style! {
+                St::FontSize => px(40),
+                St::Color => Palette::of(model).number_color(),
+                St::UserSelect => "none",
+            },
+            mv.amount.to_string(),
+        ],
+    ])
 }
 
-fn header_li(page: Page, msg: Msg) -> Node<Msg> {
-    li![
+// Negatives get a cool tile and positives a warm one, darker the further from zero, so
+// sign and rough size read at a glance against the board texture.
+fn number_card_style(value: i64, difficulty: Difficulty) -> Style {
+    let strength = (value.unsigned_abs() as f64 / difficulty.max_abs() as f64).min(1.0);
+    let (hue, saturation) = match value.signum() {
+        -1 => (215, 70),
+        1 => (15, 80),
+        _ => (0, 0),
+    };
+    style! {
+        St::Background => format!("hsl({}, {}%, {}%)", hue, saturation, 60.0 - 30.0 * strength),
+        St::Color => "white",
+        St::TextShadow => "0 1px 2px rgba(0, 0, 0, 0.5)",
+        St::Padding => "0 10px",
+        St::BorderRadius => px(8),
+        St::BoxShadow => "0 2px 4px rgba(0, 0, 0, 0.4)",
+    }
+}
+
+const ENTRANCE_STAGGER_MS: u64 = 30;
+
+// A consumed number, shown at its original position with the value it had when used. Inert
+// and hidden from screen readers: it's only a visual aid.
+fn view_used_num(model: &Model, id: usize) -> Node<Msg> {
+    let (x, y) = number_position(model, id);
+    div![
         style! {
-            St::ListStyle => "none",
-            St::MarginLeft => vw(5),
+            St::Position => "absolute",
+            St::Left => board_px(model, x as f64),
+            St::Top => board_px(model, y as f64),
+            St::MinWidth => px(44),
+            St::MinHeight => px(44),
+            St::Display => "flex",
+            St::AlignItems => "center",
+            St::JustifyContent => "center",
+            St::Opacity => "0.25",
+            St::PointerEvents => "none",
         },
-        page.to_string(),
-        ev(Ev::Click, move |_| msg),
+        attrs! {At::AriaHidden => "true"},
+        span![
+            style! {
+                St::FontSize => px(40),
+                St::Color => Palette::of(model).number_color(),
+                St::TextDecoration => "line-through",
+                St::UserSelect => "none",
+            },
+            format_number(model.game.numbers[id], &model.number_format),
+        ],
     ]
 }
 
-fn view_play(model: &Model) -> Node<Msg> {
+fn view_num(model: &Model, id: usize) -> Node<Msg> {
+    let (x, y) = number_position(model, id);
+    let is_selected = model.game.selected == Some(id)
+        || model
+            .game
+            .pending
+            .is_some_and(|(first, second)| id == first || id == second);
+    let is_changed = flashing_id(model) == Some(id);
+    let is_undo_target = model.undo_preview
+        && can_undo(model)
+        && model.history.last().is_some_and(|mv| mv.target == id);
+    let is_drop_target = model.drop_target == Some(id) && model.dragging != Some(id);
+    let is_hinted = model
+        .hint
+        .is_some_and(|(target, source)| id == target || id == source);
+    let theme = Palette::of(model);
+    let outline = theme.outline_px();
+    // In quick mode the selected number is the one that changes, so show what `id` would
+    // turn it into. Outside it the direction is only picked after the second click.
+    let preview = model
+        .game
+        .selected
+        .filter(|&target| model.quick_mode && target != id && model.hovered == Some(id))
+        .and_then(|target| {
+            let before = model.game.numbers[target];
+            let after = model.operator.apply(before, model.game.numbers[id])?;
+            Some((before, after))
+        });
+    let label = if is_selected {
+        Text::NumberSelected
+    } else if is_hinted {
+        Text::NumberHinted
+    } else {
+        Text::NumberLabel
+    };
+
+    // The outer box is the tap target: at least 44x44px so it's easy to hit on phones.
     div![
+        attrs! {
+            At::Role => "button",
+            At::TabIndex => 0,
+            At::AriaPressed => is_selected.to_string(),
+            At::AriaLabel => model.lang.format(label, &[&model.game.numbers[id]]),
+            At::Draggable => "true",
+        },
         style! {
             St::Position => "absolute",
-            St::Width => vw(100),
-            St::Height => vh(100 - HEADER_HEIGHT),
-            St::Top => vh(HEADER_HEIGHT),
+            St::Left => board_px(model, x as f64),
+            St::Top => board_px(model, y as f64),
+            St::MinWidth => px(44),
+            St::MinHeight => px(44),
+            St::Display => "flex",
+            St::AlignItems => "center",
+            St::JustifyContent => "center",
+            St::Cursor => "pointer",
+            // Overlapping boxes must not hide the selected number or the preview.
+            St::ZIndex => if preview.is_some() {"2"} else if is_selected {"1"} else {"0"},
+            // The keyframes live in index.html; staggered by id so the board fills in.
+            St::Animation => format!("algo-enter-{} 0.4s ease-out both", model.board_generation % 2),
+            St::AnimationDelay => format!("{}ms", id as u64 * ENTRANCE_STAGGER_MS),
         },
-        div![
+        IF!(is_drop_target => style! {
+            St::Outline => format!("{}px dashed {}", outline, theme.selected_color()),
+            St::BorderRadius => px(8),
+        }),
+        // Marks the selected number as the one the next click combines into.
+        IF!(model.quick_mode && model.game.selected == Some(id) => div![
             style! {
-                St::BackgroundImage => r##"url("../img/black_board.png")"##,
-                St::BackgroundRepeat => "no-repeat",
-                St::BackgroundPosition => "center top",
-                St::BackgroundSize => "contain",
-                St::Width => vw(BOARD_WIDTH),
-                St::MinWidth => vw(BOARD_WIDTH),
-                St::Height => vw(BOARD_HEIGHT),
-                St::MinHeight => vw(BOARD_HEIGHT),
-                St::Margin => "0 auto",
+                St::Position => "absolute",
+                St::Top => px(-6),
+                St::Right => px(-10),
+                St::Width => px(20),
+                St::Height => px(20),
+                St::LineHeight => px(20),
+                St::BorderRadius => "50%",
+                St::Background => theme.selected_color(),
+                St::Color => theme.on_selected_color(),
+                St::FontSize => px(16),
+                St::FontWeight => "bold",
+                St::TextAlign => "center",
+                St::PointerEvents => "none",
             },
-            izip!(&model.numbers, &model.points, &model.is_used)
-                .enumerate()
-                .filter(|(_, (_, _, &b))| !b)
-                .map(|(id, (&n, &(x, y), _))| view_num(
-                    id,
-                    n,
-                    x,
-                    y,
-                    model.selected.map(|idx| idx == id).unwrap_or(false)
-                ))
-                .collect::<Vec<_>>(),
-        ],
-        div![
-            button! {
-                "一手戻す",
-                style!{
-                    St::FontSize => px(30),
-                },
-                ev(Ev::Click, |_| Msg::ClickedRollBack),
+            model.operator.to_string(),
+        ]),
+        preview.map(|(before, after)| div![
+            style! {
+                St::Position => "absolute",
+                St::Bottom => "100%",
+                St::Left => "50%",
+                St::Transform => "translateX(-50%)",
+                St::WhiteSpace => "nowrap",
+                St::Padding => "2px 8px",
+                St::BorderRadius => px(6),
+                St::Background => "rgba(0, 0, 0, 0.75)",
+                St::Color => "white",
+                St::FontSize => px(16),
+                St::PointerEvents => "none",
             },
+            format!(
+                "{} → {}",
+                format_number(before, &model.number_format),
+                format_number(after, &model.number_format)
+            ),
+        ]),
+        span![
+            format_number(model.game.numbers[id], &model.number_format),
             style! {
-                St::Position => "relative",
-                St::Padding => "0 0 5vw 5vw",
+                St::FontSize => px(40),
+                St::Color => if is_selected {theme.selected_color()} else {theme.number_color()}
+                St::FontWeight => if model.high_contrast {"bold"} else {"normal"},
+                St::UserSelect => "none",
+                // Keeps drag enter/leave on the outer box instead of flickering over the text.
+                St::PointerEvents => "none",
             },
-            IF!(model.is_finished => view_result(model)),
+            IF!(model.card_mode => number_card_style(model.game.numbers[id], model.difficulty)),
+            IF!(model.card_mode && is_selected => style! {
+                // An outline, unlike the round border, doesn't shift the tile.
+                St::Outline => format!("{}px solid {}", outline, theme.selected_color()),
+                St::Transform => "scale(1.15)",
+                St::Transition => "transform 0.15s ease-out",
+            }),
+            IF!(!model.card_mode && is_selected => style! {
+                St::Border => format!("{}px solid {}", outline, theme.selected_color()),
+                St::BorderRadius => "50%",
+                St::Padding => "0 8px",
+                // Offsets the border and padding so the number stays in place.
+                St::Margin => format!("-{}px -{}px", outline, outline + 8),
+                St::Transform => "scale(1.15)",
+                St::Transition => "transform 0.15s ease-out, border-color 0.15s ease-out",
+            }),
+            IF!(is_hinted && !is_selected => style! {
+                St::Color => "deepskyblue",
+                St::TextShadow => "0 0 8px deepskyblue",
+            }),
+            IF!(is_changed => style! {
+                St::Color => "orange",
+                St::TextShadow => "0 0 12px orange",
+            }),
+            IF!(is_undo_target => style! {
+                St::Opacity => "0.4",
+                St::TextDecoration => "line-through",
+            }),
         ],
+        ev(Ev::Click, move |event| {
+            event.stop_propagation();
+            Msg::Clicked(id)
+        }),
+        // Enter and Space activate a focused number like a native button.
+        keyboard_ev(Ev::KeyDown, move |event| {
+            matches!(event.key().as_str(), "Enter" | " ").then(|| {
+                event.prevent_default();
+                Msg::Clicked(id)
+            })
+        }),
+        ev(Ev::MouseEnter, move |_| Msg::Hovered(Some(id))),
+        ev(Ev::MouseLeave, |_| Msg::Hovered(None)),
+        // Combines into the selected number without a second click. The browser's own menu
+        // is only replaced here, and only when there's something to combine with.
+        IF!(model.game.selected.is_some_and(|target| target != id) => mouse_ev(
+            Ev::ContextMenu,
+            move |event| {
+                event.prevent_default();
+                event.stop_propagation();
+                Msg::OpenCombineMenu {
+                    source: id,
+                    at: (f64::from(event.client_x()), f64::from(event.client_y())),
+                }
+            }
+        )),
+        // Touch browsers mostly don't fire these, so taps keep using the click flow.
+        drag_ev(Ev::DragStart, move |event| {
+            // Firefox won't start a drag without some data attached.
+            if let Some(data) = event.data_transfer() {
+                let _ = data.set_data("text/plain", &id.to_string());
+            }
+            Msg::DragStart(id)
+        }),
+        drag_ev(Ev::DragEnter, move |_| Msg::DragEntered(id)),
+        drag_ev(Ev::DragLeave, move |_| Msg::DragLeft(id)),
+        // Accepting `dragover` is what makes the box a drop target.
+        drag_ev(Ev::DragOver, |event| event.prevent_default()),
+        drag_ev(Ev::Drop, move |event| {
+            event.prevent_default();
+            Msg::DropOn(id)
+        }),
+        drag_ev(Ev::DragEnd, |_| Msg::DragEnd),
     ]
 }
 
-fn view_num(id: usize, n: i64, x: u64, y: u64, is_selected: bool) -> Node<Msg> {
-    div![
-        n.to_string(),
+// Lower is better under both scoring rules, and `previous_best` is kept per rule.
+fn view_best_comparison(model: &Model, score: i64) -> Node<Msg> {
+    let (text, color) = match model.previous_best {
+        Some(best) if score == best => (model.lang.t(Text::TiedBest).to_owned(), "inherit"),
+        Some(best) if score > best => (
+            model.lang.format(Text::BehindBest, &[&(score - best)]),
+            "inherit",
+        ),
+        _ => (model.lang.t(Text::NewBest).to_owned(), "orange"),
+    };
+    p![
         style! {
-            St::Position => "absolute",
-            St::Top => vw(x),
-            St::Left => vw(y),
-            St::FontSize => px(40),
-            St::Color => if is_selected {"yellow"} else {"lightgray"}
-            St::UserSelect => "none",
+            St::FontSize => px(30),
+            St::FontWeight => "bold",
+            St::Color => color,
         },
-        ev(Ev::Click, move |_| Msg::Clicked(id)),
+        text,
     ]
 }
 
-fn view_result(model: &Model) -> Node<Msg> {
-    let score = *model
-        .numbers
-        .iter()
-        .zip(model.is_used.iter())
-        .find(|(_, &b)| !b)
-        .unwrap()
-        .0;
+// The `algo-celebrate` keyframes live in index.html.
+fn view_perfect_banner(model: &Model) -> Node<Msg> {
     div![
         style! {
-            St::Top => vw(BOARD_HEIGHT),
-            St::FontSize => px(40),
+            St::Display => "inline-block",
+            St::Padding => "10px 30px",
+            St::BorderRadius => px(12),
+            St::Background => "linear-gradient(90deg, gold, orange, hotpink, gold)",
+            St::BackgroundSize => "300% 100%",
+            St::Color => "white",
+            St::FontWeight => "bold",
+            St::TextShadow => "0 2px 4px rgba(0, 0, 0, 0.4)",
+            St::Animation => "algo-celebrate 1.2s ease-in-out infinite alternate",
         },
-        h2!(format!("最終スコア：{}", score)),
-        p!("今回の結果を順位表に登録する↓"),
+        model.lang.t(Text::PerfectScore),
+    ]
+}
+
+fn view_result(model: &Model) -> Node<Msg> {
+    let result_style = style! {St::FontSize => px(40)};
+    let score = match final_score(model) {
+        Some(score) => score,
+        None => {
+            return div![
+                result_style,
+                p!(model.lang.t(Text::EmptyBoard)),
+                button![
+                    style! {St::FontSize => px(30)},
+                    model.lang.t(Text::NewGame),
+                    ev(Ev::Click, |_| Msg::ClickedNewGame),
+                ],
+            ]
+        }
+    };
+    div![
+        result_style,
+        // Read out as soon as the game ends.
+        attrs! {At::AriaLive => "polite"},
+        h2![
+            model.lang.format(
+                Text::FinalScore,
+                &[&format_number(score, &model.number_format)]
+            ),
+            last_mover_name(model).map(|name| span![
+                style! {St::MarginLeft => vw(2)},
+                model.lang.format(Text::DecidedBy, &[&name]),
+            ]),
+            if model.show_optimal {
+                span![
+                    style! {St::MarginLeft => vw(2)},
+                    model.lang.format(
+                        Text::Optimal,
+                        &[&format_number(initial_best(model), &model.number_format)]
+                    ),
+                    IF!(!subtraction_only(model) => model.lang.t(Text::SubtractionOnly)),
+                ]
+            } else {
+                button![
+                    style! {
+                        St::MarginLeft => vw(2),
+                        St::FontSize => px(20),
+                    },
+                    model.lang.t(Text::ShowOptimal),
+                    ev(Ev::Click, |_| Msg::ShowOptimal),
+                ]
+            },
+        ],
+        model
+            .ai
+            .as_ref()
+            .and_then(|ai| board_score(model.scoring_rule, model.target, &ai.game))
+            .map(|ai_score| p![
+                model.lang.format(Text::AiScore, &[&ai_score]),
+                span![
+                    style! {
+                        St::MarginLeft => vw(2),
+                        St::FontWeight => "bold",
+                    },
+                    // Lower scores are better.
+                    model.lang.t(match score.cmp(&ai_score) {
+                        std::cmp::Ordering::Less => Text::YouWin,
+                        std::cmp::Ordering::Greater => Text::AiWins,
+                        std::cmp::Ordering::Equal => Text::Draw,
+                    }),
+                ],
+            ]),
+        view_best_comparison(model, score),
+        IF!(model.was_optimal => view_perfect_banner(model)),
+        p!(model.lang.t(Text::RegisterPrompt)),
         div![
             input! {
+                el_ref(&model.name_input),
                 style!{
                     St::FontSize => px(30),
                     St::Margin => "0 0 3vw 3vw",
                 },
                 attrs!{
                     At::Type => "text",
-                    At::Placeholder => "ここに名前を入力してね",
+                    At::Placeholder => model.lang.t(Text::NamePlaceholder),
+                    At::Value => model.name,
+                    At::MaxLength => MAX_NAME_LEN,
                 },
-                input_ev(Ev::Input, Msg::ChangedTextArea)
+                input_ev(Ev::Input, Msg::ChangedTextArea),
+                keyboard_ev(Ev::KeyDown, {
+                    let last = model.close_result_button.clone();
+                    move |event| match event.key().as_str() {
+                        // Enter also confirms an IME conversion; only submit outside one.
+                        "Enter" if !event.is_composing() => Some(Msg::ClickedSendButton),
+                        // Shift+Tab from the first field wraps to the last button.
+                        "Tab" if event.shift_key() => {
+                            if let Some(last) = last.get() {
+                                event.prevent_default();
+                                let _ = last.focus();
+                            }
+                            None
+                        }
+                        _ => None,
+                    }
+                }),
             },
             button! {
                 style!{
                     St::FontSize => px(30),
                     St::Margin => "0 0 3vw 3vw",
                 },
-                "順位表に送信",
+                attrs!{
+                    At::Disabled => (
+                        model.submitted
+                            || model.pending_submission.is_some()
+                            || model.name.trim().is_empty()
+                    ).as_at_value(),
+                },
+                model.lang.t(if model.submitted {
+                    Text::Submitted
+                } else if model.pending_submission.is_some() {
+                    Text::Sending
+                } else {
+                    Text::SubmitRanking
+                }),
                 ev(Ev::Click, |_| Msg::ClickedSendButton),
             },
+            div![
+                style! {
+                    St::FontSize => px(20),
+                    St::Margin => "-2vw 0 2vw 3vw",
+                },
+                format!("{}/{}", model.name.chars().count(), MAX_NAME_LEN),
+            ],
+        ],
+        button! {
+            style!{
+                St::FontSize => px(30),
+                St::Margin => "0 0 3vw 3vw",
+            },
+            model.lang.t(Text::Replay),
+            ev(Ev::Click, |_| Msg::ClickedReplay),
+        },
+        button! {
+            style!{
+                St::FontSize => px(30),
+                St::Margin => "0 0 3vw 3vw",
+            },
+            model.lang.t(Text::ShowSolution),
+            ev(Ev::Click, |_| Msg::ShowSolution),
+        },
+        button! {
+            style!{
+                St::FontSize => px(30),
+                St::Margin => "0 0 3vw 3vw",
+            },
+            model.lang.t(Text::CopyResult),
+            ev(Ev::Click, |_| Msg::CopyResult),
+        },
+        IF!(model.challenge.is_some() => button! {
+            style!{
+                St::FontSize => px(30),
+                St::Margin => "0 0 3vw 3vw",
+            },
+            model.lang.t(Text::ChallengeBack),
+            ev(Ev::Click, |_| Msg::ShareChallenge),
+        }),
+        button! {
+            style!{
+                St::FontSize => px(30),
+                St::Margin => "0 0 3vw 3vw",
+            },
+            model.lang.t(Text::NewGame),
+            ev(Ev::Click, |_| Msg::ClickedNewGame),
+        },
+        button! {
+            el_ref(&model.close_result_button),
+            style!{
+                St::FontSize => px(30),
+                St::Margin => "0 0 3vw 3vw",
+            },
+            model.lang.t(Text::ViewBoard),
+            ev(Ev::Click, |_| Msg::CloseResult),
+            // Tab from the last button wraps back to the name field.
+            keyboard_ev(Ev::KeyDown, {
+                let first = model.name_input.clone();
+                move |event| {
+                    if event.key() == "Tab" && !event.shift_key() {
+                        if let Some(first) = first.get() {
+                            event.prevent_default();
+                            let _ = first.focus();
+                        }
+                    }
+                }
+            }),
+        },
+        model
+            .send_error
+            .as_ref()
+            .map(|err| p![style! {St::Color => "red"}, err]),
+        view_score_chart(model),
+    ]
+}
+
+const CHART_HEIGHT: f64 = 30.0;
+
+// One bar per recent game, scaled to the largest magnitude. Scores can be negative, so
+// the baseline moves to the middle when any are.
+fn view_score_chart(model: &Model) -> Option<Node<Msg>> {
+    let scores = &model.score_history;
+    let best = scores.iter().min()?;
+    let average = scores.iter().sum::<i64>() as f64 / scores.len() as f64;
+    let max_abs = scores.iter().map(|s| s.abs()).max().unwrap_or(0).max(1) as f64;
+    let has_negative = scores.iter().any(|&s| s < 0);
+    let (baseline, scale) = if has_negative {
+        (CHART_HEIGHT / 2.0, CHART_HEIGHT / 2.0 / max_abs)
+    } else {
+        (CHART_HEIGHT, CHART_HEIGHT / max_abs)
+    };
+    let width = 100.0 / SCORE_HISTORY_LEN as f64;
+
+    Some(div![
+        style! {St::FontSize => px(20)},
+        p![model.lang.t(Text::RecentScores)],
+        svg![
+            style! {
+                St::Width => "100%",
+                St::Height => px(120),
+            },
+            attrs! {
+                At::ViewBox => format!("0 0 100 {}", CHART_HEIGHT),
+                At::PreserveAspectRatio => "none",
+            },
+            scores
+                .iter()
+                .enumerate()
+                .map(|(i, &score)| {
+                    let height = score.abs() as f64 * scale;
+                    let y = if score < 0 {
+                        baseline
+                    } else {
+                        baseline - height
+                    };
+                    rect![attrs! {
+                        At::X => i as f64 * width + width * 0.1,
+                        At::Y => y,
+                        At::Width => width * 0.8,
+                        At::Height => height,
+                        At::Fill => if score == *best {"gold"} else {"steelblue"},
+                    }]
+                })
+                .collect::<Vec<_>>(),
+        ],
+        p![model
+            .lang
+            .format(Text::ScoreSummary, &[&format!("{:.1}", average), best])],
+    ])
+}
+
+// Every persisted preference in one place; each row is a set of mutually exclusive options.
+fn view_settings(model: &Model) -> Node<Msg> {
+    let lang = model.lang;
+    div![
+        style! {
+            St::Position => "absolute",
+            St::Width => vw(100),
+            St::Top => vh(HEADER_HEIGHT),
+            St::FontSize => px(24),
+            St::Padding => "5vw",
+            St::BoxSizing => "border-box",
+        },
+        h2!(lang.t(Text::Settings)),
+        view_settings_row(
+            lang.t(Text::ThemeSetting),
+            [Theme::Light, Theme::Dark]
+                .iter()
+                .map(|&theme| {
+                    let label = match theme {
+                        Theme::Light => Text::LightTheme,
+                        Theme::Dark => Text::DarkTheme,
+                    };
+                    settings_option(lang.t(label), theme == model.theme, || Msg::ToggleTheme)
+                })
+                .collect(),
+        ),
+        view_settings_row(
+            lang.t(Text::LanguageSetting),
+            [Lang::Ja, Lang::En]
+                .iter()
+                .map(|&option| {
+                    // Each language is listed under its own name.
+                    let label = option.toggled().t(Text::OtherLanguage);
+                    settings_option(label, option == lang, || Msg::ToggleLang)
+                })
+                .collect(),
+        ),
+        view_settings_row(
+            lang.t(Text::SoundSetting),
+            vec![
+                settings_option(lang.t(Text::On), !model.muted, || Msg::ToggleMute),
+                settings_option(lang.t(Text::Off), model.muted, || Msg::ToggleMute),
+            ],
+        ),
+        view_settings_row(lang.t(Text::HighContrastSetting), {
+            let on = model.high_contrast;
+            vec![
+                settings_option(lang.t(Text::On), on, || Msg::ToggleHighContrast),
+                settings_option(lang.t(Text::Off), !on, || Msg::ToggleHighContrast),
+            ]
+        }),
+        view_settings_row(lang.t(Text::GroupingSetting), {
+            let on = model.number_format.group_thousands;
+            vec![
+                settings_option(lang.t(Text::On), on, || Msg::ToggleGrouping),
+                settings_option(lang.t(Text::Off), !on, || Msg::ToggleGrouping),
+            ]
+        }),
+        view_settings_row(lang.t(Text::SignSetting), {
+            let on = model.number_format.explicit_sign;
+            vec![
+                settings_option(lang.t(Text::On), on, || Msg::ToggleExplicitSign),
+                settings_option(lang.t(Text::Off), !on, || Msg::ToggleExplicitSign),
+            ]
+        }),
+        view_settings_row(
+            lang.t(Text::DifficultySetting),
+            Difficulty::ALL
+                .iter()
+                .map(|&difficulty| {
+                    settings_option(
+                        lang.t(difficulty.label()),
+                        difficulty == model.difficulty,
+                        move || Msg::SelectDifficulty(difficulty),
+                    )
+                })
+                .collect(),
+        ),
+        view_settings_row(
+            lang.t(Text::ScoringSetting),
+            ScoringRule::ALL
+                .iter()
+                .map(|&rule| {
+                    settings_option(lang.t(rule.label()), rule == model.scoring_rule, || {
+                        Msg::ToggleScoringRule
+                    })
+                })
+                .collect(),
+        ),
+        IF!(model.scoring_rule.uses_target() => view_settings_row(
+            lang.t(Text::TargetSetting),
+            vec![input! {
+                style! {
+                    St::FontSize => px(20),
+                    St::Width => px(120),
+                },
+                attrs! {
+                    At::Type => "number",
+                    At::Value => model.target,
+                },
+                input_ev(Ev::Change, Msg::SetTarget),
+            }],
+        )),
+        view_stats(model),
+    ]
+}
+
+// Covers the active scoring rule only, like the personal best.
+fn view_stats(model: &Model) -> Node<Msg> {
+    let lang = model.lang;
+    let stats = &model.stats;
+    let or_dash = |value: Option<String>| value.unwrap_or_else(|| "—".to_owned());
+    div![
+        h2!(lang.t(Text::Stats)),
+        p!(lang.format(Text::GamesPlayed, &[&stats.games])),
+        p!(lang.format(
+            Text::OptimalFinishes,
+            &[&stats.optimal_games, &stats.games, &stats.optimal_percent()]
+        )),
+        p!(lang.format(
+            Text::BestScoreStat,
+            &[&or_dash(stats.best_score.map(|score| score.to_string()))]
+        )),
+        p!(lang.format(
+            Text::BestTime,
+            &[&or_dash(stats.best_time.map(|time| format!("{:.1}", time)))]
+        )),
+        p!(lang.format(
+            Text::AverageMoves,
+            &[&or_dash(
+                stats.average_moves().map(|moves| format!("{:.1}", moves))
+            )]
+        )),
+        if model.confirm_reset_stats {
+            div![
+                span![
+                    style! {St::MarginRight => px(10)},
+                    lang.t(Text::ConfirmResetStats)
+                ],
+                button![
+                    style! {St::FontSize => px(20), St::MarginRight => px(10)},
+                    lang.t(Text::ResetStats),
+                    ev(Ev::Click, |_| Msg::ConfirmResetStats(true)),
+                ],
+                button![
+                    style! {St::FontSize => px(20)},
+                    lang.t(Text::Cancel),
+                    ev(Ev::Click, |_| Msg::ConfirmResetStats(false)),
+                ],
+            ]
+        } else {
+            button![
+                style! {St::FontSize => px(20)},
+                lang.t(Text::ResetStats),
+                ev(Ev::Click, |_| Msg::ClickedResetStats),
+            ]
+        },
+    ]
+}
+
+fn view_settings_row(label: &str, options: Vec<Node<Msg>>) -> Node<Msg> {
+    div![
+        style! {
+            St::Display => "flex",
+            St::AlignItems => "center",
+            St::Gap => px(10),
+            St::MarginBottom => px(20),
+        },
+        span![style! {St::MinWidth => px(160)}, label],
+        options,
+    ]
+}
+
+// Clicking the option already in effect does nothing, so toggles can back two-way rows.
+fn settings_option(
+    label: &str,
+    selected: bool,
+    msg: impl FnOnce() -> Msg + Clone + 'static,
+) -> Node<Msg> {
+    button![
+        style! {
+            St::FontSize => px(20),
+            St::FontWeight => if selected {"bold"} else {"normal"},
+        },
+        attrs! {At::AriaPressed => selected.to_string()},
+        label,
+        ev(Ev::Click, move |_| (!selected).then(msg)),
+    ]
+}
+
+fn view_ranking(model: &Model) -> Node<Msg> {
+    let entries = sorted_ranking(model);
+
+    div![
+        style! {
+            St::Position => "absolute",
+            St::Width => vw(100),
+            St::Top => vh(HEADER_HEIGHT),
+            St::FontSize => px(30),
+            St::Padding => "5vw",
+            St::BoxSizing => "border-box",
+        },
+        div![Difficulty::ALL
+            .iter()
+            .map(|&difficulty| button![
+                style! {
+                    St::FontSize => px(24),
+                    St::MarginRight => px(10),
+                    St::FontWeight => if difficulty == model.ranking_tab {"bold"} else {"normal"},
+                },
+                attrs! {At::AriaPressed => (difficulty == model.ranking_tab).to_string()},
+                model.lang.t(difficulty.label()),
+                ev(Ev::Click, move |_| Msg::SelectRankingTab(difficulty)),
+            ])
+            .collect::<Vec<_>>()],
+        IF!(!entries.is_empty() => div![
+            style! {
+                St::FontSize => px(20),
+                St::Margin => "1vw 0",
+            },
+            model.lang.t(Text::DownloadRanking),
+            [(RankingFormat::Csv, "CSV"), (RankingFormat::Json, "JSON")]
+                .iter()
+                .map(|&(format, label)| button![
+                    style! {
+                        St::FontSize => px(20),
+                        St::MarginLeft => px(10),
+                    },
+                    label,
+                    ev(Ev::Click, move |_| Msg::DownloadRanking(format)),
+                ])
+                .collect::<Vec<_>>(),
+        ]),
+        match &model.ranking_status {
+            RankingStatus::Loading => Some(view_spinner(model)),
+            RankingStatus::Loaded => None,
+            RankingStatus::Error(err) => Some(p![
+                attrs! {At::Role => "alert"},
+                span![style! {St::Color => "red"}, err],
+                button![
+                    style! {
+                        St::FontSize => px(24),
+                        St::MarginLeft => px(10),
+                    },
+                    model.lang.t(Text::Retry),
+                    ev(Ev::Click, |_| Msg::FetchRanking),
+                ],
+            ]),
+        },
+        if entries.is_empty() {
+            // Nothing cached yet: the spinner alone says more than "no records".
+            let is_loading = matches!(model.ranking_status, RankingStatus::Loading);
+            IF!(!is_loading => p!(model.lang.t(Text::NoRecords)))
+        } else {
+            Some(table![
+                style! {St::BorderCollapse => "collapse"},
+                tr![
+                    th!(model.lang.t(Text::Rank)),
+                    th!(model.lang.t(Text::Name)),
+                    th!(model.lang.t(Text::Score)),
+                ],
+                entries
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (name, score))| tr![td!(i + 1), td!(name), td!(score.to_string()),])
+                    .collect::<Vec<_>>(),
+            ])
+        },
+    ]
+}
+
+// The `algo-spin` keyframes live in index.html.
+fn view_spinner(model: &Model) -> Node<Msg> {
+    div![
+        style! {
+            St::Display => "flex",
+            St::AlignItems => "center",
+            St::Margin => "2vw 0",
+        },
+        attrs! {At::Role => "status"},
+        div![style! {
+            St::Width => px(28),
+            St::Height => px(28),
+            St::Border => "4px solid rgba(128, 128, 128, 0.3)",
+            St::BorderTopColor => Palette::of(model).selected_color(),
+            St::BorderRadius => "50%",
+            St::Animation => "algo-spin 0.8s linear infinite",
+        }],
+        span![
+            style! {St::MarginLeft => px(10)},
+            model.lang.t(Text::Loading),
         ],
     ]
 }
@@ -303,3 +5971,226 @@ pub fn start() {
     // Mount the `app` to the element with the `id` "app".
     App::start("app", init, update, view);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEEDS: [u64; 4] = [0, 1, 42, 1_700_000_000_000];
+
+    #[test]
+    fn same_seed_gives_the_same_board() {
+        for seed in SEEDS {
+            for difficulty in Difficulty::ALL {
+                let a = generate_model(seed, difficulty, difficulty.count());
+                let b = generate_model(seed, difficulty, difficulty.count());
+                assert_eq!(a.initial_numbers, b.initial_numbers);
+                assert_eq!(a.points, b.points);
+            }
+        }
+    }
+
+    #[test]
+    fn boards_have_the_difficulty_count() {
+        for difficulty in Difficulty::ALL {
+            let model = generate_model(7, difficulty, difficulty.count());
+            assert_eq!(model.initial_numbers.len(), difficulty.count());
+            assert_eq!(model.points.len(), difficulty.count());
+            assert_eq!(model.game.numbers, model.initial_numbers);
+        }
+    }
+
+    #[test]
+    fn numbers_stay_in_range() {
+        for seed in SEEDS {
+            for difficulty in Difficulty::ALL {
+                let max_abs = difficulty.max_abs();
+                let model = generate_model(seed, difficulty, difficulty.count());
+                assert!(model.initial_numbers.iter().all(|n| n.abs() <= max_abs));
+            }
+        }
+    }
+
+    #[test]
+    fn undoing_the_finishing_move_resumes_play() {
+        let mut model = generate_model(3, Difficulty::Easy, 2);
+        let outcome = model.game.combine(0, 1, Operator::Sub);
+        let ClickOutcome::Moved { mv, finished } = outcome else {
+            panic!("expected a move, got {:?}", outcome);
+        };
+        assert!(finished);
+        record_move(&mut model, mv);
+        // What `finish_game` and a submission leave behind.
+        model.is_finished = true;
+        model.was_optimal = true;
+        model.submitted = true;
+        model.toast = Some("copied".to_owned());
+
+        assert!(roll_back(&mut model));
+        assert!(!model.is_finished);
+        assert!(!model.game.is_finished());
+        assert!(!model.was_optimal);
+        assert!(!model.submitted);
+        assert_eq!(model.toast, None);
+        assert_eq!(model.move_count, 0);
+        assert!(model.game.is_legal_click(0) && model.game.is_legal_click(1));
+        assert_eq!(model.game.numbers, model.initial_numbers);
+    }
+
+    #[test]
+    fn refinishing_after_an_undo_records_the_game_once() {
+        let mut model = generate_model(3, Difficulty::Easy, 2);
+        let finish = |model: &mut Model| {
+            let ClickOutcome::Moved { mv, .. } = model.game.combine(0, 1, Operator::Sub) else {
+                panic!("expected a move");
+            };
+            record_move(model, mv);
+            model.is_finished = true;
+            record_result(model, false)
+        };
+        assert!(finish(&mut model));
+        assert!(roll_back(&mut model));
+        assert!(!finish(&mut model));
+        assert_eq!(model.score_history.len(), 1);
+        assert_eq!(model.stats.games, 1);
+    }
+
+    #[test]
+    fn rapid_repeat_clicks_are_dropped() {
+        let mut model = generate_model(3, Difficulty::Easy, 4);
+        assert!(accept_click(&mut model, 0, 1000.0));
+        assert!(!accept_click(&mut model, 0, 1050.0));
+        // Another number, or the same one after a pause, goes through.
+        assert!(accept_click(&mut model, 1, 1060.0));
+        assert!(accept_click(&mut model, 1, 1200.0));
+        assert!(!accept_click(&mut model, 1, 1300.0));
+    }
+
+    #[test]
+    fn move_budgets_cut_the_game_short() {
+        assert_eq!(move_budget(2), 1);
+        assert_eq!(move_budget(9), 4);
+        let mut model = generate_model(3, Difficulty::Easy, 6);
+        model.move_limit = Some(move_budget(6));
+        for _ in 0..3 {
+            assert!(!is_out_of_moves(&model));
+            let live = visible_ids(&model).collect::<Vec<_>>();
+            let ClickOutcome::Moved { mv, .. } =
+                model.game.combine(live[0], live[1], Operator::Sub)
+            else {
+                panic!("expected a move");
+            };
+            record_move(&mut model, mv);
+        }
+        assert!(is_out_of_moves(&model));
+        assert_eq!(remaining_moves(&model), Some(0));
+        assert_eq!(model.game.remaining(), 3);
+    }
+
+    #[test]
+    fn phrases_hash_to_a_fixed_seed() {
+        // Pinned: changing it would change every board shared by phrase.
+        assert_eq!(text_seed("algo-party"), 4_850_550_545_052_033_115);
+        assert_eq!(text_seed("  algo-party "), text_seed("algo-party"));
+        assert_ne!(text_seed("algo-party"), text_seed("algo-Party"));
+    }
+
+    #[test]
+    fn abs_rule_measures_from_the_target() {
+        // Subtracting into one another reaches ±3, ±5 and ±9.
+        let numbers = [7, 3, 1];
+        assert_eq!(ScoringRule::Abs.solve(&numbers, 0), 3);
+        assert_eq!(ScoringRule::Abs.solve(&numbers, 5), 0);
+        assert_eq!(ScoringRule::Abs.score(-3, 2), 5);
+        // Raw ignores it.
+        assert_eq!(ScoringRule::Raw.solve(&numbers, 5), solve_best(&numbers));
+    }
+
+    #[test]
+    fn solution_lines_reach_the_optimum() {
+        let boards: [&[i64]; 4] = [
+            &[7, 3, 1],
+            &[5, 9, 2, 8],
+            &[-4, -6, -1],
+            &[3, -2, 0, 11, -7],
+        ];
+        for &numbers in &boards {
+            for &(rule, target) in &[
+                (ScoringRule::Raw, 0),
+                (ScoringRule::Abs, 0),
+                (ScoringRule::Abs, 6),
+            ] {
+                let (score, moves) = rule.solve_line(numbers, target);
+                assert_eq!(score, rule.solve(numbers, target));
+                let game = GameState::replay(numbers, &moves).expect("the line is playable");
+                assert_eq!(board_score(rule, target, &game), Some(score));
+            }
+        }
+    }
+
+    #[test]
+    fn imported_values_are_bounded() {
+        assert!(parse_board("500 1 1\n-500 2 2").is_ok());
+        assert!(matches!(
+            parse_board("3 1 1\n501 2 2"),
+            Err(BoardTextError::Value(2))
+        ));
+        assert!(matches!(
+            parse_board("-9223372036854775808 1 1\n1 2 2"),
+            Err(BoardTextError::Value(1))
+        ));
+    }
+
+    #[test]
+    fn csv_quotes_only_fields_that_need_it() {
+        let entries = [
+            ("alice".to_owned(), -12),
+            ("bob, jr".to_owned(), 3),
+            ("say \"hi\"".to_owned(), 5),
+        ];
+        let csv = ranking_to_csv(&entries.iter().collect::<Vec<_>>());
+        assert_eq!(
+            csv,
+            "rank,name,score\r\n1,alice,-12\r\n2,\"bob, jr\",3\r\n3,\"say \"\"hi\"\"\",5\r\n"
+        );
+    }
+
+    #[test]
+    fn sorted_grids_fit_the_board() {
+        for slots in NUMBER_COUNT_RANGE {
+            let positions = (0..slots)
+                .map(|rank| sorted_slot(rank, slots))
+                .collect::<HashSet<_>>();
+            assert_eq!(positions.len(), slots, "{} slots overlap", slots);
+            for (x, y) in positions {
+                assert!(x <= 100 - NUM_EDGE_MARGIN, "{} slots: x = {}", slots, x);
+                assert!(
+                    y <= BOARD_HEIGHT - NUM_EDGE_MARGIN,
+                    "{} slots: y = {}",
+                    slots,
+                    y
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn points_never_overlap() {
+        for seed in 0..200 {
+            for difficulty in Difficulty::ALL {
+                for count in [difficulty.count(), *NUMBER_COUNT_RANGE.end()] {
+                    let points = generate_model(seed, difficulty, count)
+                        .points
+                        .into_iter()
+                        .map(|(x, y)| (x as i64, y as i64))
+                        .collect::<Vec<_>>();
+                    for (i, &p) in points.iter().enumerate() {
+                        for &q in &points[i + 1..] {
+                            assert!(!overlaps(p, q), "seed {}: {:?} and {:?}", seed, p, q);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
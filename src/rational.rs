@@ -0,0 +1,121 @@
+// Exact fractions for rational mode, where `÷` would leave the integers.
+
+use crate::game::{Operator, Value};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+
+// Always in lowest terms with a positive denominator, so equal values compare equal.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub(crate) struct Rational {
+    num: i64,
+    den: i64,
+}
+
+impl Rational {
+    // `None` for a zero denominator, or if the reduced value doesn't fit.
+    pub(crate) fn new(num: i64, den: i64) -> Option<Self> {
+        Self::reduce(i128::from(num), i128::from(den))
+    }
+
+    fn reduce(num: i128, den: i128) -> Option<Self> {
+        if den == 0 {
+            return None;
+        }
+        let divisor = gcd(num, den) * den.signum();
+        Some(Self {
+            num: i64::try_from(num / divisor).ok()?,
+            den: i64::try_from(den / divisor).ok()?,
+        })
+    }
+
+    pub(crate) fn abs(self) -> Self {
+        Self {
+            num: self.num.abs(),
+            den: self.den,
+        }
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs().max(1)
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl Value for Rational {
+    fn combine(operator: Operator, lhs: Self, rhs: Self) -> Option<Self> {
+        let (a, b) = (i128::from(lhs.num), i128::from(lhs.den));
+        let (c, d) = (i128::from(rhs.num), i128::from(rhs.den));
+        match operator {
+            Operator::Add => Self::reduce(a * d + c * b, b * d),
+            Operator::Sub => Self::reduce(a * d - c * b, b * d),
+            Operator::Mul => Self::reduce(a * c, b * d),
+            Operator::Div => Self::reduce(a * d, b * c),
+        }
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Denominators are positive, so cross-multiplying keeps the order.
+        (i128::from(self.num) * i128::from(other.den))
+            .cmp(&(i128::from(other.num) * i128::from(self.den)))
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.den == 1 {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(num: i64, den: i64) -> Rational {
+        Rational::new(num, den).unwrap()
+    }
+
+    #[test]
+    fn values_are_kept_in_lowest_terms() {
+        assert_eq!(r(2, -4), r(-1, 2));
+        assert_eq!(r(0, 5), r(0, 1));
+        assert_eq!(r(6, 3).to_string(), "2");
+        assert_eq!(r(-3, 6).to_string(), "-1/2");
+    }
+
+    #[test]
+    fn arithmetic_is_exact() {
+        assert_eq!(Operator::Add.apply(r(1, 2), r(1, 3)), Some(r(5, 6)));
+        assert_eq!(Operator::Sub.apply(r(1, 2), r(1, 3)), Some(r(1, 6)));
+        assert_eq!(Operator::Mul.apply(r(2, 3), r(3, 4)), Some(r(1, 2)));
+        assert_eq!(Operator::Div.apply(r(2, 3), r(4, 9)), Some(r(3, 2)));
+    }
+
+    #[test]
+    fn dividing_by_zero_is_rejected() {
+        assert_eq!(Operator::Div.apply(r(1, 2), r(0, 1)), None);
+        assert_eq!(Rational::new(1, 0), None);
+    }
+
+    #[test]
+    fn ordering_ignores_representation() {
+        assert!(r(1, 3) < r(1, 2));
+        assert!(r(-1, 2) < r(-1, 3));
+        assert_eq!(r(-2, 3).abs(), r(2, 3));
+    }
+}
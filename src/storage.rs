@@ -0,0 +1,67 @@
+// localStorage that degrades to memory. Private browsing, a full quota or disabled storage
+// only cost persistence across reloads; the app keeps working for this session.
+
+use seed::prelude::web_storage::WebStorageError;
+use seed::prelude::{LocalStorage, WebStorage};
+use serde::{de::DeserializeOwned, Serialize};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+#[derive(Debug)]
+pub(crate) enum StorageError {
+    // The browser refused the access; the value was kept in memory instead, if written.
+    Unavailable,
+    Missing,
+    // Stored, but not in the expected shape (e.g. written by an older version).
+    Invalid,
+}
+
+thread_local! {
+    // JSON of the writes the browser refused, read back in preference to storage.
+    static FALLBACK: RefCell<HashMap<String, String>> = RefCell::default();
+    static FAILED: Cell<bool> = const { Cell::new(false) };
+    static WARNED: Cell<bool> = const { Cell::new(false) };
+}
+
+pub(crate) fn get<T: DeserializeOwned>(key: &str) -> Result<T, StorageError> {
+    if let Some(json) = FALLBACK.with(|fallback| fallback.borrow().get(key).cloned()) {
+        return serde_json::from_str(&json).map_err(|_| StorageError::Invalid);
+    }
+    LocalStorage::get(key).map_err(classify)
+}
+
+pub(crate) fn set<T: Serialize + ?Sized>(key: &str, value: &T) -> Result<(), StorageError> {
+    match LocalStorage::insert(key, value).map_err(classify) {
+        Ok(()) => {
+            FALLBACK.with(|fallback| fallback.borrow_mut().remove(key));
+            Ok(())
+        }
+        Err(StorageError::Unavailable) => {
+            let json = serde_json::to_string(value).map_err(|_| StorageError::Invalid)?;
+            FALLBACK.with(|fallback| fallback.borrow_mut().insert(key.to_owned(), json));
+            Err(StorageError::Unavailable)
+        }
+        Err(err) => Err(err),
+    }
+}
+
+pub(crate) fn remove(key: &str) -> Result<(), StorageError> {
+    FALLBACK.with(|fallback| fallback.borrow_mut().remove(key));
+    LocalStorage::remove(key).map_err(classify)
+}
+
+// `true` the first time it's called after any access failed, so the player is told once.
+pub(crate) fn take_warning() -> bool {
+    FAILED.with(Cell::get) && !WARNED.with(|warned| warned.replace(true))
+}
+
+fn classify(err: WebStorageError) -> StorageError {
+    match err {
+        WebStorageError::KeyNotFoundError => StorageError::Missing,
+        WebStorageError::JsonError(_) => StorageError::Invalid,
+        _ => {
+            FAILED.with(|failed| failed.set(true));
+            StorageError::Unavailable
+        }
+    }
+}